@@ -0,0 +1,48 @@
+//! Compare StackVec's element-wise arithmetic against hand-written loops over plain slices.
+
+#![cfg(feature = "num")]
+
+extern crate stack_vec;
+extern crate test;
+
+use stack_vec::{N256, StackVec};
+use test::Bencher;
+
+fn filled() -> StackVec<N256<f32>> {
+    let mut vec = StackVec::new();
+    for i in range(0u, 256) {
+        vec.push(i as f32 * 0.01);
+    }
+    vec
+}
+
+#[bench]
+fn stack_vec_add_assign(b: &mut Bencher) {
+    let mut a = filled();
+    let other = filled();
+    b.iter(|| a.add_assign(other.as_slice()));
+}
+
+#[bench]
+fn slice_add_assign(b: &mut Bencher) {
+    let mut a = Vec::from_fn(256, |i| i as f32 * 0.01);
+    let other = Vec::from_fn(256, |i| i as f32 * 0.01);
+    b.iter(|| {
+        for (x, y) in a.iter_mut().zip(other.iter()) { *x = *x + *y; }
+    });
+}
+
+#[bench]
+fn stack_vec_rms(b: &mut Bencher) {
+    let a = filled();
+    b.iter(|| test::black_box(a.rms()));
+}
+
+#[bench]
+fn slice_rms(b: &mut Bencher) {
+    let a = Vec::from_fn(256, |i| i as f32 * 0.01);
+    b.iter(|| {
+        let sum_sq = a.iter().fold(0.0f32, |acc, x| acc + *x * *x);
+        test::black_box((sum_sq / a.len() as f32).sqrt())
+    });
+}
@@ -0,0 +1,228 @@
+//! Sorting, searching and selection algorithms that never allocate, so that
+//! they may be used on the audio thread.
+
+use {ArrayStorage, Length, StackVec, Storage};
+
+/// Return the index of the first element of `v` for which `before` returns false,
+/// given that it returns true for some prefix of `v` and false for the rest.
+pub fn partition_point<T>(v: &[T], before: |&T| -> bool) -> uint {
+    let (mut lo, mut hi) = (0u, v.len());
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        if before(&v[mid]) { lo = mid + 1 } else { hi = mid }
+    }
+    lo
+}
+
+/// Move the first `mid` elements of `v` to its end, preserving the order of both halves.
+fn rotate_left<T>(v: &mut [T], mid: uint) {
+    v.slice_to_mut(mid).reverse();
+    v.slice_from_mut(mid).reverse();
+    v.reverse();
+}
+
+/// Stably merge the two sorted runs `v[..mid]` and `v[mid..]` without a buffer,
+/// by rotating a block of each run past the other and recursing on both sides.
+fn merge_in_place<T: Ord>(v: &mut [T], mid: uint) {
+    let len = v.len();
+    if mid == 0 || mid == len {
+        return;
+    }
+    if len == 2 {
+        if v[1] < v[0] { v.swap(0, 1); }
+        return;
+    }
+    let (cut_a, cut_b) = if mid >= len - mid {
+        let cut_a = mid / 2;
+        let cut_b = mid + partition_point(v.slice_from(mid), |e| *e < v[cut_a]);
+        (cut_a, cut_b)
+    } else {
+        let cut_b = mid + (len - mid) / 2;
+        let cut_a = partition_point(v.slice_to(mid), |e| *e <= v[cut_b]);
+        (cut_a, cut_b)
+    };
+    rotate_left(v.slice_mut(cut_a, cut_b), mid - cut_a);
+    let new_mid = cut_a + (cut_b - mid);
+    merge_in_place(v.slice_to_mut(new_mid), cut_a);
+    merge_in_place(v.slice_from_mut(new_mid), cut_b - new_mid);
+}
+
+impl<T: Ord, A: Storage<T>, L: Length> StackVec<A, L> {
+
+    /// Sort the elements in ascending order, keeping equal elements in their
+    /// original order, without allocating.
+    ///
+    /// Runs are sorted by insertion and then merged in place by rotation,
+    /// taking O(n log² n) comparisons.
+    pub fn sort_stable_in_place(&mut self) {
+        static RUN: uint = 8;
+        let v = self.as_mut_slice();
+        let len = v.len();
+        let mut start = 0;
+        while start < len {
+            let end = ::std::cmp::min(start + RUN, len);
+            for i in range(start + 1, end) {
+                let mut j = i;
+                while j > start && v[j] < v[j - 1] {
+                    v.swap(j, j - 1);
+                    j -= 1;
+                }
+            }
+            start = end;
+        }
+        let mut width = RUN;
+        while width < len {
+            let mut start = 0;
+            while start + width < len {
+                let end = ::std::cmp::min(start + 2 * width, len);
+                merge_in_place(v.slice_mut(start, end), width);
+                start = end;
+            }
+            width *= 2;
+        }
+    }
+
+    /// Reorder the elements so that the element at index `n` is the one that
+    /// would be there if sorted, with no greater elements before it and no
+    /// lesser elements after it, returning a reference to it.
+    ///
+    /// Fails if `n` is out of bounds.
+    pub fn select_nth(&mut self, n: uint) -> &T {
+        assert!(n < self.len(), "StackVec::select_nth: index out of bounds");
+        {
+            let v = self.as_mut_slice();
+            let (mut lo, mut hi) = (0u, v.len());
+            while hi - lo > 1 {
+                // Move the median of three to the end to use as the pivot.
+                let mid = lo + (hi - lo) / 2;
+                if v[mid] < v[lo] { v.swap(mid, lo); }
+                if v[hi - 1] < v[lo] { v.swap(hi - 1, lo); }
+                if v[mid] < v[hi - 1] { v.swap(mid, hi - 1); }
+                let mut store = lo;
+                for i in range(lo, hi - 1) {
+                    if v[i] < v[hi - 1] {
+                        v.swap(i, store);
+                        store += 1;
+                    }
+                }
+                v.swap(store, hi - 1);
+                if n < store { hi = store }
+                else if n > store { lo = store + 1 }
+                else { break }
+            }
+        }
+        self.get(n)
+    }
+
+    /// Move all but the first of each run of consecutive equal elements to the
+    /// end, returning the deduplicated elements followed by the duplicates.
+    pub fn partition_dedup<'a>(&'a mut self) -> (&'a mut [T], &'a mut [T]) {
+        let v = self.as_mut_slice();
+        let mut write = if v.is_empty() { 0 } else { 1 };
+        for read in range(1, v.len()) {
+            if v[read] != v[write - 1] {
+                v.swap(read, write);
+                write += 1;
+            }
+        }
+        v.split_at_mut(write)
+    }
+
+    /// Drop all but the first of each run of consecutive equal elements.
+    pub fn dedup(&mut self) {
+        let unique = {
+            let (unique, _) = self.partition_dedup();
+            unique.len()
+        };
+        while self.len() > unique {
+            self.pop();
+        }
+    }
+
+    /// Insert the given element after any equal elements within the sorted
+    /// StackVec, returning the index at which it was inserted.
+    ///
+    /// Fails if the StackVec is already full.
+    pub fn binary_search_insert(&mut self, elem: T) -> uint {
+        let idx = partition_point(self.as_slice(), |e| *e <= elem);
+        self.insert(idx, elem);
+        idx
+    }
+
+}
+
+impl<T: Ord + Clone, A: Storage<T>, L: Length> StackVec<A, L> {
+
+    /// Return a StackVec of the greatest elements in descending order,
+    /// holding as many as the resulting StackVec's storage has room for.
+    pub fn top_k<B: ArrayStorage<T>>(&self) -> StackVec<B> {
+        let mut top: StackVec<B> = StackVec::new();
+        for elem in self.iter() {
+            if top.is_full() {
+                if top.size() == 0 || *elem <= *top.get(top.len() - 1) {
+                    continue;
+                }
+                top.pop();
+            }
+            let idx = partition_point(top.as_slice(), |e| *e >= *elem);
+            top.insert(idx, elem.clone());
+        }
+        top
+    }
+
+}
+
+
+#[test]
+fn sort_select_and_dedup() {
+    use {N4, N64};
+
+    let mut vec: StackVec<N64<(uint, uint)>> = StackVec::new();
+    for i in range(0u, 50) {
+        vec.push(((i * 37) % 11, i));
+    }
+    let mut keys: StackVec<N64<uint>> = StackVec::new();
+    for &(k, _) in vec.iter() {
+        keys.push(k);
+    }
+
+    keys.sort_stable_in_place();
+    assert!(keys.as_slice().windows(2).all(|w| w[0] <= w[1]));
+    vec.sort_stable_in_place();
+    assert!(vec.as_slice().windows(2).all(|w| w[0] <= w[1]));
+
+    let top: StackVec<N4<uint>> = keys.top_k();
+    assert_eq!(top.as_slice(), [10u, 10, 10, 10].as_slice());
+
+    keys.dedup();
+    assert_eq!(keys.as_slice(), [0u, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10].as_slice());
+    assert_eq!(keys.binary_search_insert(4), 5);
+
+    let mut shuffled: StackVec<N64<uint>> = StackVec::new();
+    for i in range(0u, 40) {
+        shuffled.push((i * 17) % 40);
+    }
+    assert_eq!(*shuffled.select_nth(13), 13);
+    assert!(shuffled.as_slice().slice_to(13).iter().all(|x| *x < 13));
+}
+
+#[test]
+#[cfg(not(any(windows, target_os = "android")))]
+fn algorithms_do_not_allocate() {
+    use alloc_check::assert_no_alloc;
+    use {Compact256, N8};
+
+    let mut vec: Compact256<int> = StackVec::new();
+    assert_no_alloc(|| {
+        for i in range(0i, 200) {
+            vec.push((i * 7919) % 101 - 50);
+        }
+        vec.sort_stable_in_place();
+        assert!(vec.as_slice().windows(2).all(|w| w[0] <= w[1]));
+        let top: StackVec<N8<int>> = vec.top_k();
+        assert_eq!(*top.get(0), 50);
+        vec.dedup();
+        assert_eq!(*vec.select_nth(0), -50);
+        vec.binary_search_insert(0);
+    });
+}
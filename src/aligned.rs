@@ -0,0 +1,107 @@
+//! Storage with a guaranteed alignment, for feeding StackVecs directly to SIMD code.
+
+use std::mem;
+use std::raw;
+use {Array, Length, StackVec, Storage};
+
+/// A marker type whose alignment is 16 bytes.
+#[simd]
+#[deriving(Clone)]
+pub struct A16(u64, u64);
+
+/// A marker type whose alignment is 32 bytes.
+#[simd]
+#[deriving(Clone)]
+pub struct A32(u64, u64, u64, u64);
+
+/// A marker type whose alignment is 64 bytes.
+#[simd]
+#[deriving(Clone)]
+pub struct A64(u64, u64, u64, u64, u64, u64, u64, u64);
+
+/// A StackVec whose elements begin at an address aligned to the alignment of `M`.
+///
+/// e.g. `AlignedStackVec<[f32, ..256], A32>` may be loaded from with aligned AVX instructions.
+pub type AlignedStackVec<A, M> = StackVec<Array<A, M>>;
+
+/// Return the number of `T` lanes within each `V`, checking that a slice of
+/// `T` starting at `ptr` may be viewed as a slice of `V`.
+fn lanes_of<T, V>(ptr: *const T) -> uint {
+    let (t_size, v_size) = (mem::size_of::<T>(), mem::size_of::<V>());
+    assert!(t_size > 0 && v_size > 0 && v_size % t_size == 0,
+            "the chunk type must be a whole number of elements wide");
+    assert!(ptr as uint % mem::min_align_of::<V>() == 0,
+            "the elements are not sufficiently aligned for the chunk type");
+    v_size / t_size
+}
+
+impl<T, A: Storage<T>, L: Length> StackVec<A, L> {
+
+    /// View the elements as a slice of aligned chunks of type `V`, followed by
+    /// the remaining elements that don't fill a whole chunk.
+    ///
+    /// `V` should be a SIMD vector or fixed-size array of `T`, e.g. `f32x4` or
+    /// `[f32, ..8]`. Fails if the elements are not aligned for `V`, which an
+    /// AlignedStackVec with an alignment of at least that of `V` guarantees.
+    pub fn as_aligned_chunks<'a, V>(&'a self) -> (&'a [V], &'a [T]) {
+        let slice = self.as_slice();
+        let lanes = lanes_of::<T, V>(slice.as_ptr());
+        let n_chunks = slice.len() / lanes;
+        let chunks: &'a [V] = unsafe {
+            mem::transmute(raw::Slice { data: slice.as_ptr() as *const V, len: n_chunks })
+        };
+        (chunks, slice.slice_from(n_chunks * lanes))
+    }
+
+    /// View the elements as a mutable slice of aligned chunks of type `V`,
+    /// followed by the remaining elements that don't fill a whole chunk.
+    ///
+    /// See `as_aligned_chunks` for the requirements upon `V`.
+    pub fn as_aligned_chunks_mut<'a, V>(&'a mut self) -> (&'a mut [V], &'a mut [T]) {
+        let slice = self.as_mut_slice();
+        let lanes = lanes_of::<T, V>(slice.as_ptr());
+        let n_chunks = slice.len() / lanes;
+        let (chunks, rest) = slice.split_at_mut(n_chunks * lanes);
+        let chunks: &'a mut [V] = unsafe {
+            mem::transmute(raw::Slice { data: chunks.as_ptr() as *const V, len: n_chunks })
+        };
+        (chunks, rest)
+    }
+
+}
+
+
+#[test]
+fn aligned_chunks() {
+    let mut vec: AlignedStackVec<[f32, ..16], A64> = StackVec::new();
+    assert_eq!(vec.storage().as_ptr() as uint % 64, 0);
+    assert_eq!(vec.storage().alignment(), 64);
+    for i in range(0u, 10) {
+        vec.push(i as f32);
+    }
+    {
+        let (chunks, rest) = vec.as_aligned_chunks_mut::<[f32, ..4]>();
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(rest.len(), 2);
+        assert_eq!(rest[0], 8.0);
+        for chunk in chunks.iter_mut() {
+            chunk[0] = -1.0;
+        }
+    }
+    assert_eq!(vec.as_slice()[4], -1.0);
+    let (chunks, _) = vec.as_aligned_chunks::<[f32, ..8]>();
+    assert_eq!(chunks.len(), 1);
+}
+
+#[test]
+fn elements_are_aligned_past_the_drop_flag() {
+    // The Array's drop flag is a single byte, far smaller than the alignment,
+    // and each StackVec follows a single byte within its tuple.
+    let pair: (u8, AlignedStackVec<[u8, ..16], A16>) = (0, StackVec::new());
+    let (_, ref bytes) = pair;
+    assert_eq!(bytes.as_slice().as_ptr() as uint % 16, 0);
+    let mut pair: (u8, AlignedStackVec<[f32, ..16], A32>) = (0, StackVec::new());
+    let (_, ref mut floats) = pair;
+    floats.push(1.0);
+    assert_eq!(floats.as_slice().as_ptr() as uint % 32, 0);
+}
@@ -0,0 +1,53 @@
+//! Checks that the crate's collections really do stay off the heap.
+//!
+//! The standard library allocates through jemalloc, which keeps a count of
+//! the bytes allocated by each thread. A closure that leaves the count
+//! unchanged has not allocated.
+//!
+//! The count is read through `je_mallctl`, which only exists where the
+//! standard library allocates through jemalloc, so the checks are compiled
+//! out on Windows and Android. The module depends upon nothing within the
+//! crate so that the integration tests may include it too.
+
+use libc::{c_char, c_int, c_void, size_t};
+use std::mem;
+use std::ptr;
+
+extern {
+    fn je_mallctl(name: *const c_char, oldp: *mut c_void, oldlenp: *mut size_t,
+                  newp: *mut c_void, newlen: size_t) -> c_int;
+}
+
+/// Return the number of bytes allocated by the current thread so far.
+fn allocated() -> u64 {
+    let name = b"thread.allocated\0";
+    let mut bytes = 0u64;
+    let mut len = mem::size_of::<u64>() as size_t;
+    let status = unsafe {
+        je_mallctl(name.as_ptr() as *const c_char, &mut bytes as *mut u64 as *mut c_void,
+                   &mut len, ptr::null_mut(), 0)
+    };
+    assert!(status == 0, "alloc_check: jemalloc's thread.allocated statistic is unavailable");
+    bytes
+}
+
+/// Call `f` and return its result, failing if it allocated.
+pub fn assert_no_alloc<R>(f: || -> R) -> R {
+    // The first query may set up jemalloc's per-thread state.
+    allocated();
+    let before = allocated();
+    let result = f();
+    let after = allocated();
+    assert!(after == before, "alloc_check: {} bytes were allocated", after - before);
+    result
+}
+
+
+#[test]
+#[should_fail]
+fn allocation_is_detected() {
+    assert_no_alloc(|| {
+        let vec: Vec<uint> = Vec::with_capacity(16);
+        vec
+    });
+}
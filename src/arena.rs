@@ -0,0 +1,150 @@
+//! A bump allocator for scratch values, backed by a fixed-size byte buffer.
+
+use std::cell::{Cell, UnsafeCell};
+use std::kinds::marker;
+use std::mem;
+use std::ptr;
+use std::raw;
+use {ArrayStorage, Storage};
+
+/// An arena that allocates values of any type from a fixed-size byte buffer.
+///
+/// Each allocation simply bumps an offset into the buffer, rounding it up to
+/// the alignment of the allocated type. Allocations are freed all at once by
+/// `reset`, e.g. at the end of each audio block.
+///
+/// Values allocated within the arena are never dropped, so the arena is best
+/// suited to plain data.
+pub struct StackArena<A> {
+    bytes: UnsafeCell<A>,
+    offset: Cell<uint>,
+    no_sync: marker::NoSync,
+}
+
+impl<A: ArrayStorage<u8>> StackArena<A> {
+
+    /// Create an empty StackArena.
+    #[inline]
+    pub fn new() -> StackArena<A> {
+        StackArena::from_storage(ArrayStorage::new())
+    }
+
+}
+
+impl<A: Storage<u8>> StackArena<A> {
+
+    /// Create an empty StackArena over the given byte storage.
+    #[inline]
+    pub fn from_storage(bytes: A) -> StackArena<A> {
+        StackArena { bytes: UnsafeCell::new(bytes), offset: Cell::new(0), no_sync: marker::NoSync }
+    }
+
+    /// Return the size of the buffer in bytes.
+    #[inline]
+    pub fn capacity(&self) -> uint { unsafe { (*self.bytes.get()).capacity() } }
+
+    /// Return the number of bytes used so far, including any alignment padding.
+    #[inline]
+    pub fn used(&self) -> uint { self.offset.get() }
+
+    /// Return the number of bytes that have not yet been used.
+    #[inline]
+    pub fn remaining(&self) -> uint { self.capacity() - self.used() }
+
+    /// Claim space for `count` values of type `T`, returning a pointer to the first.
+    fn bump<T>(&self, count: uint) -> Option<*mut T> {
+        let base = unsafe { (*self.bytes.get()).as_mut_ptr() };
+        let align = mem::min_align_of::<T>();
+        let addr = base as uint + self.offset.get();
+        let padding = (align - addr % align) % align;
+        let start = self.offset.get() + padding;
+        let end = match mem::size_of::<T>().checked_mul(&count).and_then(|n| start.checked_add(&n)) {
+            Some(end) if end <= self.capacity() => end,
+            _ => return None,
+        };
+        self.offset.set(end);
+        Some(unsafe { base.offset(start as int) as *mut T })
+    }
+
+    /// Move the given value into the arena, returning a mutable reference to it.
+    ///
+    /// Fails if the arena does not have room for the value.
+    pub fn alloc<'a, T>(&'a self, value: T) -> &'a mut T {
+        match self.try_alloc(value) {
+            Ok(value) => value,
+            Err(_) => fail!("StackArena::alloc: capacity of {} bytes exhausted", self.capacity()),
+        }
+    }
+
+    /// Move the given value into the arena, returning a mutable reference to it.
+    ///
+    /// If the arena does not have room for the value, it is handed back as `Err`.
+    pub fn try_alloc<'a, T>(&'a self, value: T) -> Result<&'a mut T, T> {
+        match self.bump::<T>(1) {
+            Some(ptr) => unsafe {
+                ptr::write(ptr, value);
+                Ok(&mut *ptr)
+            },
+            None => Err(value),
+        }
+    }
+
+    /// Copy the given elements into the arena, returning a mutable slice of the copies.
+    ///
+    /// Fails if the arena does not have room for the elements.
+    pub fn alloc_slice<'a, T: Clone>(&'a self, elems: &[T]) -> &'a mut [T] {
+        match self.try_alloc_slice(elems) {
+            Ok(slice) => slice,
+            Err(_) => fail!("StackArena::alloc_slice: capacity of {} bytes exhausted", self.capacity()),
+        }
+    }
+
+    /// Copy the given elements into the arena, returning a mutable slice of the copies.
+    ///
+    /// If the arena does not have room for the elements, they are handed back as `Err`.
+    pub fn try_alloc_slice<'a, 'b, T: Clone>(&'a self, elems: &'b [T])
+        -> Result<&'a mut [T], &'b [T]>
+    {
+        match self.bump::<T>(elems.len()) {
+            Some(ptr) => unsafe {
+                for (i, elem) in elems.iter().enumerate() {
+                    ptr::write(ptr.offset(i as int), elem.clone());
+                }
+                Ok(mem::transmute(raw::Slice { data: ptr as *const T, len: elems.len() }))
+            },
+            None => Err(elems),
+        }
+    }
+
+    /// Free every allocation, allowing the whole buffer to be used again.
+    ///
+    /// Requiring `&mut self` ensures that no references into the arena remain.
+    #[inline]
+    pub fn reset(&mut self) { self.offset.set(0); }
+
+}
+
+
+#[test]
+fn bump_align_and_reset() {
+    use N64;
+
+    let mut arena: StackArena<N64<u8>> = StackArena::new();
+    {
+        let byte = arena.alloc(1u8);
+        let word = arena.alloc(2u64);
+        assert_eq!(*byte, 1);
+        assert_eq!(*word, 2);
+        assert_eq!(word as *mut u64 as uint % mem::min_align_of::<u64>(), 0);
+
+        let samples = arena.alloc_slice([0.5f32, 0.25, 0.125].as_slice());
+        samples[0] = 1.0;
+        assert_eq!(samples.as_slice(), [1.0f32, 0.25, 0.125].as_slice());
+        let wide = [0u64, ..8];
+        assert_eq!(arena.try_alloc_slice(wide.as_slice()).err().map(|e| e.len()), Some(8));
+        assert_eq!(arena.try_alloc([0u8, ..64]).is_err(), true);
+    }
+    arena.reset();
+    assert_eq!(arena.used(), 0);
+    assert!(arena.try_alloc([0u8, ..64]).is_ok());
+}
@@ -0,0 +1,236 @@
+//! A fixed-size set of bits stored within an array of words.
+
+use std::iter::FromIterator;
+use {ArrayStorage, StackVec, Storage};
+
+/// The number of bits stored within each word.
+pub static WORD_BITS: uint = 64;
+
+/// A trait to be implemented for the fixed-size word arrays that back a StackBitSet.
+pub trait BitSetWords {
+    /// Return an array with every bit cleared.
+    fn zeroed() -> Self;
+    /// Return the words as a slice.
+    fn words(&self) -> &[u64];
+    /// Return the words as a mutable slice.
+    fn words_mut(&mut self) -> &mut [u64];
+}
+
+pub type B64 = [u64, ..1];
+pub type B128 = [u64, ..2];
+pub type B256 = [u64, ..4];
+pub type B512 = [u64, ..8];
+pub type B1024 = [u64, ..16];
+
+impl_bit_set_words!(1)
+impl_bit_set_words!(2)
+impl_bit_set_words!(4)
+impl_bit_set_words!(8)
+impl_bit_set_words!(16)
+
+/// A set of small integers stored as bits within a fixed-size array of words.
+#[deriving(Clone, PartialEq, Eq)]
+pub struct StackBitSet<W> {
+    words: W,
+}
+
+impl<W: BitSetWords> StackBitSet<W> {
+
+    /// Create a StackBitSet with every bit cleared.
+    #[inline]
+    pub fn new() -> StackBitSet<W> {
+        StackBitSet { words: BitSetWords::zeroed() }
+    }
+
+    /// Return the number of bits the StackBitSet can hold.
+    #[inline]
+    pub fn capacity(&self) -> uint { self.words.words().len() * WORD_BITS }
+
+    /// Return the number of set bits.
+    pub fn count_ones(&self) -> uint {
+        self.words.words().iter().fold(0, |n, w| n + w.count_ones())
+    }
+
+    /// Return true if no bits are set.
+    pub fn is_empty(&self) -> bool {
+        self.words.words().iter().all(|w| *w == 0)
+    }
+
+    /// Return true if the given bit is set.
+    #[inline]
+    pub fn contains(&self, bit: uint) -> bool {
+        self.words.words()[bit / WORD_BITS] & (1 << (bit % WORD_BITS)) != 0
+    }
+
+    /// Set the given bit, returning true if it was not already set.
+    ///
+    /// Fails if the bit lies beyond the capacity of the StackBitSet.
+    #[inline]
+    pub fn insert(&mut self, bit: uint) -> bool {
+        let word = &mut self.words.words_mut()[bit / WORD_BITS];
+        let mask = 1 << (bit % WORD_BITS);
+        let was_clear = *word & mask == 0;
+        *word |= mask;
+        was_clear
+    }
+
+    /// Clear the given bit, returning true if it was set.
+    #[inline]
+    pub fn remove(&mut self, bit: uint) -> bool {
+        let word = &mut self.words.words_mut()[bit / WORD_BITS];
+        let mask = 1 << (bit % WORD_BITS);
+        let was_set = *word & mask != 0;
+        *word &= !mask;
+        was_set
+    }
+
+    /// Clear every bit.
+    pub fn clear(&mut self) {
+        for w in self.words.words_mut().iter_mut() {
+            *w = 0;
+        }
+    }
+
+    /// Return the lowest set bit.
+    pub fn first_set(&self) -> Option<uint> {
+        for (i, w) in self.words.words().iter().enumerate() {
+            if *w != 0 {
+                return Some(i * WORD_BITS + w.trailing_zeros());
+            }
+        }
+        None
+    }
+
+    /// Return the lowest cleared bit.
+    pub fn first_clear(&self) -> Option<uint> {
+        for (i, w) in self.words.words().iter().enumerate() {
+            if *w != !0 {
+                return Some(i * WORD_BITS + (!*w).trailing_zeros());
+            }
+        }
+        None
+    }
+
+    /// Return an iterator over the set bits in ascending order.
+    #[inline]
+    pub fn iter<'a>(&'a self) -> Bits<'a> {
+        let words = self.words.words();
+        Bits { words: words, idx: 0, current: if words.len() > 0 { words[0] } else { 0 } }
+    }
+
+    /// Set every bit that is set within `other`.
+    pub fn union_with(&mut self, other: &StackBitSet<W>) {
+        self.combine_with(other, |a, b| a | b)
+    }
+
+    /// Clear every bit that is not set within `other`.
+    pub fn intersect_with(&mut self, other: &StackBitSet<W>) {
+        self.combine_with(other, |a, b| a & b)
+    }
+
+    /// Clear every bit that is set within `other`.
+    pub fn difference_with(&mut self, other: &StackBitSet<W>) {
+        self.combine_with(other, |a, b| a & !b)
+    }
+
+    /// Keep only the bits that are set within exactly one of the two sets.
+    pub fn symmetric_difference_with(&mut self, other: &StackBitSet<W>) {
+        self.combine_with(other, |a, b| a ^ b)
+    }
+
+    /// Combine each of our words with the corresponding word of `other`.
+    #[inline]
+    fn combine_with(&mut self, other: &StackBitSet<W>, f: |u64, u64| -> u64) {
+        let others = other.words.words();
+        for (w, o) in self.words.words_mut().iter_mut().zip(others.iter()) {
+            *w = f(*w, *o);
+        }
+    }
+
+    /// Return true if every bit set within `self` is also set within `other`.
+    pub fn is_subset(&self, other: &StackBitSet<W>) -> bool {
+        self.words.words().iter().zip(other.words.words().iter()).all(|(a, b)| *a & !*b == 0)
+    }
+
+    /// Return true if the sets have no bits in common.
+    pub fn is_disjoint(&self, other: &StackBitSet<W>) -> bool {
+        self.words.words().iter().zip(other.words.words().iter()).all(|(a, b)| *a & *b == 0)
+    }
+
+    /// Collect the indices of the set bits into a StackVec in ascending order.
+    ///
+    /// Fails if there are more set bits than the StackVec can hold.
+    pub fn to_stack_vec<A: ArrayStorage<uint>>(&self) -> StackVec<A> {
+        let mut vec = StackVec::new();
+        for bit in self.iter() {
+            vec.push(bit);
+        }
+        vec
+    }
+
+    /// Create a StackBitSet with the bits at each of the given indices set.
+    pub fn from_stack_vec<A: Storage<uint>>(indices: &StackVec<A>) -> StackBitSet<W> {
+        indices.iter().map(|bit| *bit).collect()
+    }
+
+}
+
+impl<W: BitSetWords> FromIterator<uint> for StackBitSet<W> {
+    fn from_iter<I: Iterator<uint>>(iterator: I) -> StackBitSet<W> {
+        let mut set = StackBitSet::new();
+        for bit in iterator {
+            set.insert(bit);
+        }
+        set
+    }
+}
+
+/// An iterator over the set bits of a StackBitSet.
+pub struct Bits<'a> {
+    words: &'a [u64],
+    idx: uint,
+    current: u64,
+}
+
+impl<'a> Iterator<uint> for Bits<'a> {
+    #[inline]
+    fn next(&mut self) -> Option<uint> {
+        while self.current == 0 {
+            self.idx += 1;
+            if self.idx >= self.words.len() {
+                return None;
+            }
+            self.current = self.words[self.idx];
+        }
+        let bit = self.current.trailing_zeros();
+        // Clear the lowest set bit.
+        self.current &= self.current - 1;
+        Some(self.idx * WORD_BITS + bit)
+    }
+}
+
+
+#[test]
+fn bits_and_indices() {
+    use N8;
+
+    let mut a: StackBitSet<B128> = StackBitSet::new();
+    assert_eq!(a.capacity(), 128);
+    assert!(a.insert(3) && a.insert(64) && a.insert(127));
+    assert!(!a.insert(64));
+    assert_eq!(a.count_ones(), 3);
+    assert_eq!(a.first_set(), Some(3));
+    assert_eq!(a.first_clear(), Some(0));
+    assert_eq!(a.iter().collect::<Vec<uint>>(), vec![3, 64, 127]);
+
+    let b: StackBitSet<B128> = vec![0u, 3, 100].into_iter().collect();
+    let mut c = a.clone();
+    c.intersect_with(&b);
+    assert_eq!(c.iter().collect::<Vec<uint>>(), vec![3]);
+    c.symmetric_difference_with(&a);
+    assert_eq!(c.iter().collect::<Vec<uint>>(), vec![64, 127]);
+
+    let indices: StackVec<N8<uint>> = a.to_stack_vec();
+    assert_eq!(indices.len(), 3);
+    assert!(StackBitSet::from_stack_vec(&indices) == a);
+}
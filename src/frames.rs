@@ -0,0 +1,209 @@
+//! Buffers of interleaved multichannel frames, as commonly used for audio.
+
+use std::num::Num;
+use std::slice;
+use {ArrayStorage, StackVec, Storage};
+
+/// A buffer of interleaved frames, each holding one sample per channel.
+///
+/// The samples live within a single StackVec laid out frame by frame, i.e.
+/// `[l0, r0, l1, r1, ..]` for a stereo buffer. The number of channels is set
+/// upon creation, and the maximum number of frames is the storage's capacity
+/// divided by the number of channels.
+pub struct StackFrames<A> {
+    channels: uint,
+    samples: StackVec<A>,
+}
+
+impl<S: Copy, A: ArrayStorage<S>> StackFrames<A> {
+
+    /// Create an empty buffer of frames with the given number of channels.
+    #[inline]
+    pub fn new(channels: uint) -> StackFrames<A> {
+        assert!(channels > 0, "StackFrames::new: there must be at least one channel");
+        StackFrames { channels: channels, samples: StackVec::new() }
+    }
+
+    /// Return the number of channels within each frame.
+    #[inline]
+    pub fn channels(&self) -> uint { self.channels }
+
+    /// Return the number of frames in the buffer.
+    #[inline]
+    pub fn len(&self) -> uint { self.samples.len() / self.channels }
+
+    /// Return true if the buffer contains no frames.
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.samples.is_empty() }
+
+    /// Return the maximum number of frames that the buffer can hold.
+    #[inline]
+    pub fn size(&self) -> uint { self.samples.size() / self.channels }
+
+    /// Return all samples in their interleaved order.
+    #[inline]
+    pub fn as_slice<'a>(&'a self) -> &'a [S] { self.samples.as_slice() }
+
+    /// Return all samples in their interleaved order as a mutable slice.
+    #[inline]
+    pub fn as_mut_slice<'a>(&'a mut self) -> &'a mut [S] { self.samples.as_mut_slice() }
+
+    /// Push a frame onto the end of the buffer.
+    ///
+    /// Fails if the frame's length differs from the number of channels or if the buffer is full.
+    pub fn push_frame(&mut self, frame: &[S]) {
+        assert!(frame.len() == self.channels, "StackFrames::push_frame: wrong number of channels");
+        assert!(self.len() < self.size(), "StackFrames::push_frame: capacity exceeded");
+        for sample in frame.iter() {
+            self.samples.push(*sample);
+        }
+    }
+
+    /// Return the samples of the frame at the given index.
+    #[inline]
+    pub fn frame<'a>(&'a self, idx: uint) -> &'a [S] {
+        let start = idx * self.channels;
+        self.as_slice().slice(start, start + self.channels)
+    }
+
+    /// Return the samples of the frame at the given index as a mutable slice.
+    #[inline]
+    pub fn frame_mut<'a>(&'a mut self, idx: uint) -> &'a mut [S] {
+        let (start, channels) = (idx * self.channels, self.channels);
+        self.as_mut_slice().slice_mut(start, start + channels)
+    }
+
+    /// Return an iterator over the samples of the given channel.
+    #[inline]
+    pub fn channel<'a>(&'a self, channel: uint) -> Channel<'a, S> {
+        assert!(channel < self.channels, "StackFrames::channel: no such channel");
+        Channel { samples: self.as_slice().slice_from(channel).iter(), step: self.channels }
+    }
+
+    /// Return an iterator over the samples of the given channel that allows modifying each sample.
+    #[inline]
+    pub fn channel_mut<'a>(&'a mut self, channel: uint) -> ChannelMut<'a, S> {
+        assert!(channel < self.channels, "StackFrames::channel_mut: no such channel");
+        let step = self.channels;
+        ChannelMut { samples: self.as_mut_slice().slice_from_mut(channel).iter_mut(), step: step }
+    }
+
+    /// Replace the contents of the buffer by interleaving the given per-channel StackVecs.
+    ///
+    /// Fails if the number of StackVecs differs from the number of channels,
+    /// if their lengths differ or if they hold more frames than will fit.
+    pub fn interleave_from<B: Storage<S>>(&mut self, channels: &[StackVec<B>]) {
+        assert!(channels.len() == self.channels, "StackFrames::interleave_from: wrong number of channels");
+        let frames = channels[0].len();
+        assert!(channels.iter().all(|c| c.len() == frames),
+                "StackFrames::interleave_from: the channels must be of equal length");
+        assert!(frames <= self.size(), "StackFrames::interleave_from: capacity exceeded");
+        self.samples.clear();
+        for i in range(0, frames) {
+            for channel in channels.iter() {
+                self.samples.push(*channel.get(i));
+            }
+        }
+    }
+
+    /// Replace the contents of each of the given StackVecs with the samples of its channel.
+    ///
+    /// Fails if the number of StackVecs differs from the number of channels.
+    pub fn deinterleave_into<B: Storage<S>>(&self, channels: &mut [StackVec<B>]) {
+        assert!(channels.len() == self.channels, "StackFrames::deinterleave_into: wrong number of channels");
+        for (c, channel) in channels.iter_mut().enumerate() {
+            channel.clear();
+            for sample in self.channel(c) {
+                channel.push(*sample);
+            }
+        }
+    }
+
+    /// Remove all frames from the buffer.
+    #[inline]
+    pub fn clear(&mut self) { self.samples.clear(); }
+
+}
+
+impl<S: Num + Copy, A: ArrayStorage<S>> StackFrames<A> {
+
+    /// Add the samples of channel `src`, multiplied by the given gain, onto those of channel `dst`.
+    pub fn mix_channel(&mut self, src: uint, dst: uint, gain: S) {
+        assert!(src < self.channels && dst < self.channels, "StackFrames::mix_channel: no such channel");
+        let channels = self.channels;
+        for frame in self.as_mut_slice().chunks_mut(channels) {
+            frame[dst] = frame[dst] + frame[src] * gain;
+        }
+    }
+
+    /// Multiply each sample of the given channel by the given gain.
+    pub fn scale_channel(&mut self, channel: uint, gain: S) {
+        for sample in self.channel_mut(channel) {
+            *sample = *sample * gain;
+        }
+    }
+
+}
+
+/// An iterator over the samples of a single channel.
+pub struct Channel<'a, S: 'a> {
+    samples: slice::Items<'a, S>,
+    step: uint,
+}
+
+impl<'a, S> Iterator<&'a S> for Channel<'a, S> {
+    #[inline]
+    fn next(&mut self) -> Option<&'a S> {
+        let sample = self.samples.next();
+        if sample.is_some() {
+            for _ in range(1, self.step) { self.samples.next(); }
+        }
+        sample
+    }
+}
+
+/// An iterator over the samples of a single channel that allows modifying each sample.
+pub struct ChannelMut<'a, S: 'a> {
+    samples: slice::MutItems<'a, S>,
+    step: uint,
+}
+
+impl<'a, S> Iterator<&'a mut S> for ChannelMut<'a, S> {
+    #[inline]
+    fn next(&mut self) -> Option<&'a mut S> {
+        let sample = self.samples.next();
+        if sample.is_some() {
+            for _ in range(1, self.step) { self.samples.next(); }
+        }
+        sample
+    }
+}
+
+
+#[test]
+fn interleave_round_trip() {
+    use {N4, N16};
+
+    let mut left: StackVec<N4<f32>> = StackVec::new();
+    let mut right: StackVec<N4<f32>> = StackVec::new();
+    for i in range(0u, 3) {
+        left.push(i as f32);
+        right.push(-(i as f32));
+    }
+
+    let mut frames: StackFrames<N16<f32>> = StackFrames::new(2);
+    frames.interleave_from([left, right].as_slice());
+    assert_eq!(frames.len(), 3);
+    assert_eq!(frames.size(), 8);
+    assert_eq!(frames.frame(2), [2.0f32, -2.0].as_slice());
+    assert_eq!(frames.channel(1).map(|s| *s).collect::<Vec<f32>>(), vec![0.0f32, -1.0, -2.0]);
+
+    frames.mix_channel(0, 1, 1.0);
+    frames.scale_channel(0, 0.5);
+    frames.push_frame([4.0f32, 4.0].as_slice());
+
+    let mut split: [StackVec<N4<f32>>, ..2] = [StackVec::new(), StackVec::new()];
+    frames.deinterleave_into(split.as_mut_slice());
+    assert_eq!(split[0].as_slice(), [0.0f32, 0.5, 1.0, 4.0].as_slice());
+    assert_eq!(split[1].as_slice(), [0.0f32, 0.0, 0.0, 4.0].as_slice());
+}
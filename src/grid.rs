@@ -0,0 +1,219 @@
+//! A fixed-capacity two-dimensional grid, e.g. for step sequencer patterns or convolution kernels.
+
+use std::slice;
+use {ArrayStorage, StackVec};
+
+/// A grid of elements laid out row by row within a StackVec.
+///
+/// The number of rows and columns is set upon creation and must fit within
+/// the capacity of the storage, e.g. a `StackGrid<N16<f32>>` may be 4x4 or 2x8.
+pub struct StackGrid<A> {
+    rows: uint,
+    cols: uint,
+    elems: StackVec<A>,
+}
+
+impl<T: Clone, A: ArrayStorage<T>> StackGrid<A> {
+
+    /// Create a grid of the given dimensions with every element set to `elem`.
+    ///
+    /// Fails if either dimension is zero, as rows of zero width cannot be
+    /// iterated over, or if the grid does not fit within the storage.
+    pub fn from_elem(rows: uint, cols: uint, elem: T) -> StackGrid<A> {
+        assert!(rows > 0 && cols > 0, "StackGrid::from_elem: dimensions must be non-zero");
+        let mut elems = StackVec::new();
+        assert!(rows * cols <= elems.size(), "StackGrid::from_elem: capacity exceeded");
+        for _ in range(0, rows * cols) {
+            elems.push(elem.clone());
+        }
+        StackGrid { rows: rows, cols: cols, elems: elems }
+    }
+
+    /// Return a new grid whose rows are the columns of this grid.
+    pub fn transpose(&self) -> StackGrid<A> {
+        let mut elems = StackVec::new();
+        for col in range(0, self.cols) {
+            for elem in self.column(col) {
+                elems.push(elem.clone());
+            }
+        }
+        StackGrid { rows: self.cols, cols: self.rows, elems: elems }
+    }
+
+}
+
+impl<T, A: ArrayStorage<T>> StackGrid<A> {
+
+    /// Return the number of rows.
+    #[inline]
+    pub fn rows(&self) -> uint { self.rows }
+
+    /// Return the number of columns.
+    #[inline]
+    pub fn cols(&self) -> uint { self.cols }
+
+    /// Return all elements in row-major order.
+    #[inline]
+    pub fn as_slice<'a>(&'a self) -> &'a [T] { self.elems.as_slice() }
+
+    /// Return all elements in row-major order as a mutable slice.
+    #[inline]
+    pub fn as_mut_slice<'a>(&'a mut self) -> &'a mut [T] { self.elems.as_mut_slice() }
+
+    /// Return an immutable reference to the element at the given row and column.
+    #[inline]
+    pub fn get(&self, row: uint, col: uint) -> &T {
+        assert!(row < self.rows && col < self.cols, "StackGrid::get: index out of bounds");
+        self.elems.get(row * self.cols + col)
+    }
+
+    /// Return a mutable reference to the element at the given row and column.
+    #[inline]
+    pub fn get_mut(&mut self, row: uint, col: uint) -> &mut T {
+        assert!(row < self.rows && col < self.cols, "StackGrid::get_mut: index out of bounds");
+        let cols = self.cols;
+        self.elems.get_mut(row * cols + col)
+    }
+
+    /// Return the elements of the given row.
+    #[inline]
+    pub fn row<'a>(&'a self, row: uint) -> &'a [T] {
+        assert!(row < self.rows, "StackGrid::row: no such row");
+        let start = row * self.cols;
+        self.as_slice().slice(start, start + self.cols)
+    }
+
+    /// Return the elements of the given row as a mutable slice.
+    #[inline]
+    pub fn row_mut<'a>(&'a mut self, row: uint) -> &'a mut [T] {
+        assert!(row < self.rows, "StackGrid::row_mut: no such row");
+        let (start, cols) = (row * self.cols, self.cols);
+        self.as_mut_slice().slice_mut(start, start + cols)
+    }
+
+    /// Return an iterator over the elements of the given column.
+    #[inline]
+    pub fn column<'a>(&'a self, col: uint) -> Column<'a, T> {
+        assert!(col < self.cols, "StackGrid::column: no such column");
+        Column { elems: self.as_slice().slice_from(col).iter(), step: self.cols }
+    }
+
+    /// Return an iterator over the rows, each as a slice.
+    #[inline]
+    pub fn iter_rows<'a>(&'a self) -> slice::Chunks<'a, T> {
+        self.as_slice().chunks(self.cols)
+    }
+
+    /// Return an iterator over the columns, each as an iterator of its elements.
+    #[inline]
+    pub fn iter_columns<'a>(&'a self) -> Columns<'a, A> {
+        Columns { grid: self, col: 0 }
+    }
+
+    /// Return an iterator over the rows of the region beginning at the given
+    /// row and column that spans the given number of rows and columns, each
+    /// as a slice.
+    pub fn region<'a>(&'a self, row: uint, col: uint, rows: uint, cols: uint) -> Region<'a, T> {
+        assert!(row + rows <= self.rows && col + cols <= self.cols,
+                "StackGrid::region: the region exceeds the grid");
+        Region { rows: self.iter_rows().skip(row).take(rows), col: col, cols: cols }
+    }
+
+}
+
+impl<T, A: ArrayStorage<T>> Index<(uint, uint), T> for StackGrid<A> {
+    #[inline]
+    fn index<'a>(&'a self, index: &(uint, uint)) -> &'a T {
+        let &(row, col) = index;
+        self.get(row, col)
+    }
+}
+
+impl<T, A: ArrayStorage<T>> IndexMut<(uint, uint), T> for StackGrid<A> {
+    #[inline]
+    fn index_mut<'a>(&'a mut self, index: &(uint, uint)) -> &'a mut T {
+        let &(row, col) = index;
+        self.get_mut(row, col)
+    }
+}
+
+/// An iterator over the elements of a single column.
+pub struct Column<'a, T: 'a> {
+    elems: slice::Items<'a, T>,
+    step: uint,
+}
+
+impl<'a, T> Iterator<&'a T> for Column<'a, T> {
+    #[inline]
+    fn next(&mut self) -> Option<&'a T> {
+        let elem = self.elems.next();
+        if elem.is_some() {
+            for _ in range(1, self.step) { self.elems.next(); }
+        }
+        elem
+    }
+}
+
+/// An iterator over the columns of a grid.
+pub struct Columns<'a, A: 'a> {
+    grid: &'a StackGrid<A>,
+    col: uint,
+}
+
+impl<'a, T, A: ArrayStorage<T>> Iterator<Column<'a, T>> for Columns<'a, A> {
+    #[inline]
+    fn next(&mut self) -> Option<Column<'a, T>> {
+        if self.col == self.grid.cols {
+            return None;
+        }
+        self.col += 1;
+        Some(self.grid.column(self.col - 1))
+    }
+}
+
+/// An iterator over the rows of a rectangular region of a grid.
+pub struct Region<'a, T: 'a> {
+    rows: ::std::iter::Take<::std::iter::Skip<slice::Chunks<'a, T>>>,
+    col: uint,
+    cols: uint,
+}
+
+impl<'a, T> Iterator<&'a [T]> for Region<'a, T> {
+    #[inline]
+    fn next(&mut self) -> Option<&'a [T]> {
+        let (col, cols) = (self.col, self.cols);
+        self.rows.next().map(|row| row.slice(col, col + cols))
+    }
+}
+
+
+#[test]
+fn rows_columns_and_regions() {
+    use N16;
+
+    let mut grid: StackGrid<N16<uint>> = StackGrid::from_elem(3, 4, 0);
+    for row in range(0u, 3) {
+        for col in range(0u, 4) {
+            grid[(row, col)] = row * 10 + col;
+        }
+    }
+    assert_eq!(grid.row(1), [10u, 11, 12, 13].as_slice());
+    assert_eq!(grid.column(2).map(|x| *x).collect::<Vec<uint>>(), vec![2, 12, 22]);
+    assert_eq!(grid.iter_columns().count(), 4);
+
+    let region: Vec<&[uint]> = grid.region(1, 1, 2, 2).collect();
+    assert_eq!(region, vec![[11u, 12].as_slice(), [21u, 22].as_slice()]);
+
+    let transposed = grid.transpose();
+    assert_eq!((transposed.rows(), transposed.cols()), (4, 3));
+    assert_eq!(transposed[(3, 1)], 13);
+    assert_eq!(transposed.row(0), [0u, 10, 20].as_slice());
+}
+
+#[test]
+#[should_fail]
+fn zero_width_grids_are_rejected() {
+    use N16;
+
+    let _grid: StackGrid<N16<uint>> = StackGrid::from_elem(4, 0, 0);
+}
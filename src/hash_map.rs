@@ -0,0 +1,343 @@
+//! An open-addressing hash map stored within fixed-size stack storage.
+
+use std::hash::{Hash, Hasher};
+use std::hash::sip::SipHasher;
+use std::mem;
+use StackVecArray;
+
+/// A hash map using linear probing over a fixed-size array of slots.
+///
+/// The map never resizes: once every slot is occupied, insertion of a new key
+/// is refused. Removal shifts the following entries of the probe sequence back
+/// rather than leaving tombstones, so lookups never degrade over time.
+pub struct StackHashMap<A, H = SipHasher> {
+    len: uint,
+    slots: A,
+    hasher: H,
+}
+
+impl<K: Hash + Eq, V, A: StackVecArray<(K, V)>> StackHashMap<A, SipHasher> {
+
+    /// Create an empty StackHashMap using the default SipHasher.
+    #[inline]
+    pub fn new() -> StackHashMap<A, SipHasher> {
+        StackHashMap::with_hasher(SipHasher::new())
+    }
+
+}
+
+impl<K: Eq + Hash<S>, V, S, H: Hasher<S>, A: StackVecArray<(K, V)>> StackHashMap<A, H> {
+
+    /// Create an empty StackHashMap which will use the given hasher to hash keys.
+    #[inline]
+    pub fn with_hasher(hasher: H) -> StackHashMap<A, H> {
+        StackHashMap { len: 0, slots: StackVecArray::new(), hasher: hasher }
+    }
+
+    /// Return a reference to the map's hasher.
+    #[inline]
+    pub fn hasher(&self) -> &H { &self.hasher }
+
+    /// Return the number of key-value pairs within the StackHashMap.
+    #[inline]
+    pub fn len(&self) -> uint { self.len }
+
+    /// Return true if the StackHashMap contains no pairs.
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.len == 0 }
+
+    /// Return true if every slot is occupied.
+    #[inline]
+    pub fn is_full(&self) -> bool { self.len == self.slots.size() }
+
+    /// Return the maximum number of pairs the StackHashMap can hold.
+    #[inline]
+    pub fn capacity(&self) -> uint { self.slots.size() }
+
+    /// Return the slot at which the probe sequence for the given key begins.
+    #[inline]
+    fn home(&self, key: &K) -> uint {
+        (self.hasher.hash(key) % self.slots.size() as u64) as uint
+    }
+
+    /// Return the slot following the given one, wrapping around the end.
+    #[inline]
+    fn next_slot(&self, idx: uint) -> uint {
+        (idx + 1) % self.slots.size()
+    }
+
+    /// Walk the probe sequence for the given key.
+    ///
+    /// Returns `Ok` with the key's slot if found. Otherwise returns `Err` with
+    /// the first free slot of the sequence, or `None` if every slot is occupied.
+    fn search(&self, key: &K) -> Result<uint, Option<uint>> {
+        let mut idx = self.home(key);
+        for _ in range(0, self.slots.size()) {
+            if !self.slots.is_occupied(idx) {
+                return Err(Some(idx));
+            }
+            let &(ref k, _) = self.slots.get(idx);
+            if k == key {
+                return Ok(idx);
+            }
+            idx = self.next_slot(idx);
+        }
+        Err(None)
+    }
+
+    /// Remove the pair at the given slot, shifting back any following pairs
+    /// whose probe sequence passes through the freed slot.
+    fn remove_slot(&mut self, idx: uint) -> (K, V) {
+        let cap = self.slots.size();
+        let removed = self.slots.remove(idx);
+        self.len -= 1;
+        let mut hole = idx;
+        let mut idx = self.next_slot(idx);
+        while self.slots.is_occupied(idx) {
+            let home = {
+                let &(ref k, _) = self.slots.get(idx);
+                self.home(k)
+            };
+            // Only move the pair if the hole lies between its home slot and its current slot.
+            if (hole + cap - home) % cap < (idx + cap - home) % cap {
+                let pair = self.slots.remove(idx);
+                self.slots.set(hole, pair);
+                hole = idx;
+            }
+            idx = self.next_slot(idx);
+        }
+        removed
+    }
+
+    /// Return true if the StackHashMap contains the given key.
+    #[inline]
+    pub fn contains_key(&self, key: &K) -> bool { self.search(key).is_ok() }
+
+    /// Return an immutable reference to the value for the given key.
+    pub fn get(&self, key: &K) -> Option<&V> {
+        match self.search(key) {
+            Ok(idx) => {
+                let &(_, ref v) = self.slots.get(idx);
+                Some(v)
+            },
+            Err(_) => None,
+        }
+    }
+
+    /// Return a mutable reference to the value for the given key.
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        match self.search(key) {
+            Ok(idx) => match *self.slots.get_mut(idx) {
+                (_, ref mut v) => Some(v),
+            },
+            Err(_) => None,
+        }
+    }
+
+    /// Insert a key-value pair, returning the previous value for the key if there was one.
+    ///
+    /// Fails if the key is new and every slot is already occupied.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        match self.try_insert(key, value) {
+            Ok(old) => old,
+            Err(_) => fail!("StackHashMap::insert: capacity of {} exceeded", self.capacity()),
+        }
+    }
+
+    /// Insert a key-value pair, returning the previous value for the key if there was one.
+    ///
+    /// If the key is new and every slot is occupied, the pair is handed back as `Err`.
+    pub fn try_insert(&mut self, key: K, value: V) -> Result<Option<V>, (K, V)> {
+        match self.search(&key) {
+            Ok(idx) => match *self.slots.get_mut(idx) {
+                (_, ref mut v) => Ok(Some(mem::replace(v, value))),
+            },
+            Err(Some(idx)) => {
+                self.slots.set(idx, (key, value));
+                self.len += 1;
+                Ok(None)
+            },
+            Err(None) => Err((key, value)),
+        }
+    }
+
+    /// Remove the pair for the given key, returning its value if there was one.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        match self.search(key) {
+            Ok(idx) => {
+                let (_, v) = self.remove_slot(idx);
+                Some(v)
+            },
+            Err(_) => None,
+        }
+    }
+
+    /// Remove all pairs from the StackHashMap.
+    pub fn clear(&mut self) {
+        for idx in range(0, self.slots.size()) {
+            if self.slots.is_occupied(idx) {
+                self.slots.remove(idx);
+            }
+        }
+        self.len = 0;
+    }
+
+    /// Return the entry for the given key for in-place manipulation.
+    pub fn entry<'a>(&'a mut self, key: K) -> Entry<'a, K, V, A, H> {
+        match self.search(&key) {
+            Ok(idx) => Occupied(OccupiedEntry { map: self, idx: idx }),
+            Err(idx) => Vacant(VacantEntry { map: self, key: key, idx: idx }),
+        }
+    }
+
+    /// Return an iterator over the pairs in slot order.
+    #[inline]
+    pub fn iter<'a>(&'a self) -> Entries<'a, K, V, A> {
+        Entries { slots: &self.slots, idx: 0, remaining: self.len }
+    }
+
+}
+
+/// A struct for iterating over a StackHashMap's pairs.
+pub struct Entries<'a, K, V, A: 'a> {
+    slots: &'a A,
+    idx: uint,
+    remaining: uint,
+}
+
+impl<'a, K, V, A: StackVecArray<(K, V)> + 'a> Iterator<(&'a K, &'a V)> for Entries<'a, K, V, A> {
+    #[inline]
+    fn next(&mut self) -> Option<(&'a K, &'a V)> {
+        while self.remaining > 0 {
+            let idx = self.idx;
+            self.idx += 1;
+            if self.slots.is_occupied(idx) {
+                self.remaining -= 1;
+                let &(ref k, ref v) = self.slots.get(idx);
+                return Some((k, v));
+            }
+        }
+        None
+    }
+    #[inline]
+    fn size_hint(&self) -> (uint, Option<uint>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+/// A view into a single key's slot within a StackHashMap.
+pub enum Entry<'a, K: 'a, V: 'a, A: 'a, H: 'a> {
+    /// The key is present within the map.
+    Occupied(OccupiedEntry<'a, K, V, A, H>),
+    /// The key is absent from the map.
+    Vacant(VacantEntry<'a, K, V, A, H>),
+}
+
+/// A view into an occupied entry of a StackHashMap.
+pub struct OccupiedEntry<'a, K: 'a, V: 'a, A: 'a, H: 'a> {
+    map: &'a mut StackHashMap<A, H>,
+    idx: uint,
+}
+
+/// A view into a vacant entry of a StackHashMap.
+pub struct VacantEntry<'a, K: 'a, V: 'a, A: 'a, H: 'a> {
+    map: &'a mut StackHashMap<A, H>,
+    key: K,
+    idx: Option<uint>,
+}
+
+impl<'a, K: Eq + Hash<S>, V, S, H: Hasher<S>, A: StackVecArray<(K, V)>> OccupiedEntry<'a, K, V, A, H> {
+
+    /// Return an immutable reference to the entry's value.
+    pub fn get(&self) -> &V {
+        let &(_, ref v) = self.map.slots.get(self.idx);
+        v
+    }
+
+    /// Return a mutable reference to the entry's value.
+    pub fn get_mut(&mut self) -> &mut V {
+        match *self.map.slots.get_mut(self.idx) {
+            (_, ref mut v) => v,
+        }
+    }
+
+    /// Convert the entry into a mutable reference to its value.
+    pub fn into_mut(self) -> &'a mut V {
+        let OccupiedEntry { map, idx } = self;
+        match *map.slots.get_mut(idx) {
+            (_, ref mut v) => v,
+        }
+    }
+
+    /// Replace the entry's value, returning the old value.
+    pub fn insert(&mut self, value: V) -> V {
+        mem::replace(self.get_mut(), value)
+    }
+
+    /// Remove the entry from the map, returning its value.
+    pub fn take(self) -> V {
+        let (_, v) = self.map.remove_slot(self.idx);
+        v
+    }
+
+}
+
+impl<'a, K: Eq + Hash<S>, V, S, H: Hasher<S>, A: StackVecArray<(K, V)>> VacantEntry<'a, K, V, A, H> {
+
+    /// Insert the entry's key with the given value, returning a mutable reference to the value.
+    ///
+    /// Fails if every slot of the StackHashMap is already occupied.
+    pub fn insert(self, value: V) -> &'a mut V {
+        match self.try_insert(value) {
+            Ok(v) => v,
+            Err(_) => fail!("VacantEntry::insert: StackHashMap capacity exceeded"),
+        }
+    }
+
+    /// Insert the entry's key with the given value, handing the pair back if every slot is occupied.
+    pub fn try_insert(self, value: V) -> Result<&'a mut V, (K, V)> {
+        let VacantEntry { map, key, idx } = self;
+        match idx {
+            Some(idx) => {
+                map.slots.set(idx, (key, value));
+                map.len += 1;
+                match *map.slots.get_mut(idx) {
+                    (_, ref mut v) => Ok(v),
+                }
+            },
+            None => Err((key, value)),
+        }
+    }
+
+}
+
+
+#[test]
+fn fill_and_drain() {
+    use Slots8;
+
+    let mut map: StackHashMap<Slots8<(uint, uint)>> = StackHashMap::new();
+    for i in range(0u, 8) {
+        assert_eq!(map.insert(i, i * 10), None);
+    }
+    assert!(map.is_full());
+    assert_eq!(map.try_insert(8, 80), Err((8, 80)));
+    assert_eq!(map.try_insert(3, 33), Ok(Some(30)));
+
+    // Removing from a full table exercises the backward shift of colliding pairs.
+    for i in range(0u, 8).filter(|i| *i % 2 == 0) {
+        assert_eq!(map.remove(&i), Some(i * 10));
+    }
+    assert_eq!(map.len(), 4);
+    for i in range(0u, 8) {
+        assert_eq!(map.contains_key(&i), i % 2 == 1);
+    }
+    assert_eq!(map.get(&3), Some(&33));
+    assert_eq!(map.iter().count(), 4);
+
+    match map.entry(0) {
+        Vacant(entry) => *entry.insert(1) += 1,
+        Occupied(_) => fail!(),
+    }
+    assert_eq!(map.get(&0), Some(&2));
+}
@@ -0,0 +1,227 @@
+//! An open-addressing hash set stored within fixed-size stack storage.
+
+use hash_map::{Entries, Occupied, StackHashMap, Vacant};
+use std::hash::{Hash, Hasher};
+use std::hash::sip::SipHasher;
+use std::iter::{Chain, FromIterator};
+use StackVecArray;
+
+/// A hash set using linear probing over a fixed-size array of slots.
+///
+/// The set is a StackHashMap whose values are all `()`, so its slots hold
+/// `(T, ())` pairs, e.g. `StackHashSet<Slots8<(uint, ())>>`. Like the map, it
+/// never resizes and removal shifts the following elements of the probe
+/// sequence back rather than leaving tombstones.
+pub struct StackHashSet<A, H = SipHasher> {
+    map: StackHashMap<A, H>,
+}
+
+impl<T: Hash + Eq, A: StackVecArray<(T, ())>> StackHashSet<A, SipHasher> {
+
+    /// Create an empty StackHashSet using the default SipHasher.
+    #[inline]
+    pub fn new() -> StackHashSet<A, SipHasher> {
+        StackHashSet::with_hasher(SipHasher::new())
+    }
+
+}
+
+impl<T: Eq + Hash<S>, S, H: Hasher<S>, A: StackVecArray<(T, ())>> StackHashSet<A, H> {
+
+    /// Create an empty StackHashSet which will use the given hasher to hash elements.
+    #[inline]
+    pub fn with_hasher(hasher: H) -> StackHashSet<A, H> {
+        StackHashSet { map: StackHashMap::with_hasher(hasher) }
+    }
+
+    /// Return a reference to the set's hasher.
+    #[inline]
+    pub fn hasher(&self) -> &H { self.map.hasher() }
+
+    /// Return the number of elements within the StackHashSet.
+    #[inline]
+    pub fn len(&self) -> uint { self.map.len() }
+
+    /// Return true if the StackHashSet contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.map.is_empty() }
+
+    /// Return true if every slot is occupied.
+    #[inline]
+    pub fn is_full(&self) -> bool { self.map.is_full() }
+
+    /// Return the maximum number of elements the StackHashSet can hold.
+    #[inline]
+    pub fn capacity(&self) -> uint { self.map.capacity() }
+
+    /// Return true if the StackHashSet contains the given value.
+    #[inline]
+    pub fn contains(&self, value: &T) -> bool { self.map.contains_key(value) }
+
+    /// Insert the given value, returning true if it was not already present.
+    ///
+    /// Fails if the value is new and every slot is already occupied.
+    pub fn insert(&mut self, value: T) -> bool {
+        match self.try_insert(value) {
+            Ok(inserted) => inserted,
+            Err(_) => fail!("StackHashSet::insert: capacity of {} exceeded", self.capacity()),
+        }
+    }
+
+    /// Insert the given value, returning true if it was not already present.
+    ///
+    /// If the value is new and every slot is occupied, it is handed back as `Err`.
+    pub fn try_insert(&mut self, value: T) -> Result<bool, T> {
+        match self.map.entry(value) {
+            Occupied(_) => Ok(false),
+            Vacant(entry) => match entry.try_insert(()) {
+                Ok(_) => Ok(true),
+                Err((value, ())) => Err(value),
+            },
+        }
+    }
+
+    /// Remove the given value, returning true if it was present.
+    #[inline]
+    pub fn remove(&mut self, value: &T) -> bool { self.map.remove(value).is_some() }
+
+    /// Remove all elements from the StackHashSet.
+    #[inline]
+    pub fn clear(&mut self) { self.map.clear() }
+
+    /// Return an iterator over the elements in slot order.
+    #[inline]
+    pub fn iter<'a>(&'a self) -> SetItems<'a, T, A> {
+        SetItems { entries: self.map.iter() }
+    }
+
+    /// Return an iterator over the elements in either set.
+    #[inline]
+    pub fn union<'a>(&'a self, other: &'a StackHashSet<A, H>) -> Union<'a, T, A, H> {
+        self.iter().chain(other.difference(self))
+    }
+
+    /// Return an iterator over the elements in both sets.
+    #[inline]
+    pub fn intersection<'a>(&'a self, other: &'a StackHashSet<A, H>) -> Intersection<'a, T, A, H> {
+        Intersection { iter: self.iter(), other: other }
+    }
+
+    /// Return an iterator over the elements in `self` but not in `other`.
+    #[inline]
+    pub fn difference<'a>(&'a self, other: &'a StackHashSet<A, H>) -> Difference<'a, T, A, H> {
+        Difference { iter: self.iter(), other: other }
+    }
+
+    /// Return an iterator over the elements in exactly one of the sets.
+    #[inline]
+    pub fn symmetric_difference<'a>(&'a self, other: &'a StackHashSet<A, H>)
+        -> SymmetricDifference<'a, T, A, H>
+    {
+        self.difference(other).chain(other.difference(self))
+    }
+
+    /// Return true if every element of `self` is also in `other`.
+    pub fn is_subset(&self, other: &StackHashSet<A, H>) -> bool {
+        self.iter().all(|value| other.contains(value))
+    }
+
+    /// Return true if the sets have no elements in common.
+    pub fn is_disjoint(&self, other: &StackHashSet<A, H>) -> bool {
+        self.iter().all(|value| !other.contains(value))
+    }
+
+}
+
+impl<T: Eq + Hash, A: StackVecArray<(T, ())>> FromIterator<T> for StackHashSet<A, SipHasher> {
+    fn from_iter<I: Iterator<T>>(iterator: I) -> StackHashSet<A, SipHasher> {
+        let mut set = StackHashSet::new();
+        for value in iterator {
+            set.insert(value);
+        }
+        set
+    }
+}
+
+/// A struct for iterating over a StackHashSet's elements.
+pub struct SetItems<'a, T, A: 'a> {
+    entries: Entries<'a, T, (), A>,
+}
+
+impl<'a, T, A: StackVecArray<(T, ())> + 'a> Iterator<&'a T> for SetItems<'a, T, A> {
+    #[inline]
+    fn next(&mut self) -> Option<&'a T> {
+        self.entries.next().map(|(value, _)| value)
+    }
+    #[inline]
+    fn size_hint(&self) -> (uint, Option<uint>) { self.entries.size_hint() }
+}
+
+/// An iterator over the intersection of two StackHashSets.
+pub struct Intersection<'a, T, A: 'a, H: 'a> {
+    iter: SetItems<'a, T, A>,
+    other: &'a StackHashSet<A, H>,
+}
+
+/// An iterator over the difference of two StackHashSets.
+pub struct Difference<'a, T, A: 'a, H: 'a> {
+    iter: SetItems<'a, T, A>,
+    other: &'a StackHashSet<A, H>,
+}
+
+/// An iterator over the union of two StackHashSets.
+pub type Union<'a, T, A, H> = Chain<SetItems<'a, T, A>, Difference<'a, T, A, H>>;
+
+/// An iterator over the symmetric difference of two StackHashSets.
+pub type SymmetricDifference<'a, T, A, H> = Chain<Difference<'a, T, A, H>, Difference<'a, T, A, H>>;
+
+impl<'a, T: Eq + Hash<S>, S, H: Hasher<S>, A: StackVecArray<(T, ())> + 'a> Iterator<&'a T>
+for Intersection<'a, T, A, H> {
+    #[inline]
+    fn next(&mut self) -> Option<&'a T> {
+        loop {
+            match self.iter.next() {
+                Some(value) if self.other.contains(value) => return Some(value),
+                Some(_) => (),
+                None => return None,
+            }
+        }
+    }
+}
+
+impl<'a, T: Eq + Hash<S>, S, H: Hasher<S>, A: StackVecArray<(T, ())> + 'a> Iterator<&'a T>
+for Difference<'a, T, A, H> {
+    #[inline]
+    fn next(&mut self) -> Option<&'a T> {
+        loop {
+            match self.iter.next() {
+                Some(value) if !self.other.contains(value) => return Some(value),
+                Some(_) => (),
+                None => return None,
+            }
+        }
+    }
+}
+
+
+#[test]
+fn hashed_set_algebra() {
+    use Slots8;
+
+    let a: StackHashSet<Slots8<(uint, ())>> = vec![1u, 3, 5, 7].into_iter().collect();
+    let b: StackHashSet<Slots8<(uint, ())>> = vec![3u, 4, 5, 6].into_iter().collect();
+
+    let mut union: Vec<uint> = a.union(&b).map(|x| *x).collect();
+    union.sort();
+    assert_eq!(union, vec![1, 3, 4, 5, 6, 7]);
+    let mut intersection: Vec<uint> = a.intersection(&b).map(|x| *x).collect();
+    intersection.sort();
+    assert_eq!(intersection, vec![3, 5]);
+    let mut symmetric: Vec<uint> = a.symmetric_difference(&b).map(|x| *x).collect();
+    symmetric.sort();
+    assert_eq!(symmetric, vec![1, 4, 6, 7]);
+
+    let mut c = a;
+    assert!(c.remove(&3) && !c.remove(&3));
+    assert!(!c.contains(&3) && c.contains(&1) && c.contains(&5) && c.contains(&7));
+}
@@ -0,0 +1,216 @@
+//! A vector that stores its elements inline within a StackVec until it runs
+//! out of room, at which point it spills them onto the heap.
+
+use std::mem;
+use {ArrayStorage, Items, MutItems, StackVec};
+
+/// Where a HybridVec's elements currently live.
+enum Data<T, A> {
+    Inline(StackVec<A>),
+    Spilled(Vec<T>),
+}
+
+/// A vector that behaves like a StackVec until its inline storage is full.
+///
+/// Rather than failing when an element is pushed onto a full StackVec, the
+/// elements are moved into a heap-allocated `Vec` and the HybridVec carries on
+/// from there. This suits buffers that rarely, but occasionally, exceed their
+/// usual size.
+pub struct HybridVec<T, A> {
+    data: Data<T, A>,
+}
+
+impl<T, A: ArrayStorage<T>> HybridVec<T, A> {
+
+    /// Create an empty HybridVec using its inline storage.
+    #[inline]
+    pub fn new() -> HybridVec<T, A> {
+        HybridVec { data: Inline(StackVec::new()) }
+    }
+
+    /// Return true if the elements have been moved onto the heap.
+    #[inline]
+    pub fn spilled(&self) -> bool {
+        match self.data {
+            Inline(_) => false,
+            Spilled(_) => true,
+        }
+    }
+
+    /// Move all elements from the inline storage onto the heap.
+    fn spill(&mut self) {
+        let vec = match self.data {
+            Inline(ref mut stack) => {
+                let mut vec = Vec::with_capacity(stack.size() * 2);
+                while !stack.is_empty() {
+                    vec.push(stack.pop());
+                }
+                vec.reverse();
+                vec
+            },
+            Spilled(_) => return,
+        };
+        self.data = Spilled(vec);
+    }
+
+    /// Move the elements back into the inline storage if they fit, freeing the heap allocation.
+    ///
+    /// Returns true if the elements are stored inline afterwards.
+    pub fn shrink_to_inline(&mut self) -> bool {
+        let mut stack: StackVec<A> = StackVec::new();
+        let vec = match self.data {
+            Inline(_) => return true,
+            Spilled(ref mut vec) => {
+                if vec.len() > stack.size() {
+                    return false;
+                }
+                mem::replace(vec, Vec::new())
+            },
+        };
+        for elem in vec.into_iter() {
+            stack.push(elem);
+        }
+        self.data = Inline(stack);
+        true
+    }
+
+    /// Push an element onto the end of the HybridVec, spilling onto the heap if necessary.
+    #[inline]
+    pub fn push(&mut self, elem: T) {
+        let full = match self.data {
+            Inline(ref stack) => stack.is_full(),
+            Spilled(_) => false,
+        };
+        if full {
+            self.spill();
+        }
+        match self.data {
+            Inline(ref mut stack) => stack.push(elem),
+            Spilled(ref mut vec) => vec.push(elem),
+        }
+    }
+
+    /// Remove and return the final element.
+    #[inline]
+    pub fn pop(&mut self) -> T {
+        match self.data {
+            Inline(ref mut stack) => stack.pop(),
+            Spilled(ref mut vec) => vec.pop().expect("HybridVec::pop: the vector is empty"),
+        }
+    }
+
+    /// Return the number of elements in the HybridVec.
+    #[inline]
+    pub fn len(&self) -> uint {
+        match self.data {
+            Inline(ref stack) => stack.len(),
+            Spilled(ref vec) => vec.len(),
+        }
+    }
+
+    /// Return true if the HybridVec contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.len() == 0 }
+
+    /// Return the number of elements that can be held without spilling or reallocating.
+    #[inline]
+    pub fn size(&self) -> uint {
+        match self.data {
+            Inline(ref stack) => stack.size(),
+            Spilled(ref vec) => vec.capacity(),
+        }
+    }
+
+    /// Return the elements as a slice.
+    #[inline]
+    pub fn as_slice<'a>(&'a self) -> &'a [T] {
+        match self.data {
+            Inline(ref stack) => stack.as_slice(),
+            Spilled(ref vec) => vec.as_slice(),
+        }
+    }
+
+    /// Return the elements as a mutable slice.
+    #[inline]
+    pub fn as_mut_slice<'a>(&'a mut self) -> &'a mut [T] {
+        match self.data {
+            Inline(ref mut stack) => stack.as_mut_slice(),
+            Spilled(ref mut vec) => vec.as_mut_slice(),
+        }
+    }
+
+    /// Return an iterator over the elements.
+    #[inline]
+    pub fn iter<'a>(&'a self) -> Items<'a, T> { self.as_slice().iter() }
+
+    /// Return an iterator over the elements that allows modifying each value.
+    #[inline]
+    pub fn iter_mut<'a>(&'a mut self) -> MutItems<'a, T> { self.as_mut_slice().iter_mut() }
+
+    /// Return an immutable reference to the value at the given index.
+    #[inline]
+    pub fn get(&self, idx: uint) -> &T { &self.as_slice()[idx] }
+
+    /// Return a mutable reference to the value at the given index.
+    #[inline]
+    pub fn get_mut(&mut self, idx: uint) -> &mut T { &mut self.as_mut_slice()[idx] }
+
+    /// Set the given index with the given element, dropping the element it replaces.
+    #[inline]
+    pub fn set(&mut self, idx: uint, elem: T) { *self.get_mut(idx) = elem; }
+
+    /// Insert an element at the given index, shifting all following elements to the right.
+    pub fn insert(&mut self, idx: uint, elem: T) {
+        let full = match self.data {
+            Inline(ref stack) => stack.is_full(),
+            Spilled(_) => false,
+        };
+        if full {
+            self.spill();
+        }
+        match self.data {
+            Inline(ref mut stack) => stack.insert(idx, elem),
+            Spilled(ref mut vec) => vec.insert(idx, elem),
+        }
+    }
+
+    /// Remove the element at the given index, shifting all following elements to the left.
+    pub fn remove(&mut self, idx: uint) -> T {
+        match self.data {
+            Inline(ref mut stack) => stack.remove(idx),
+            Spilled(ref mut vec) => vec.remove(idx).expect("HybridVec::remove: index out of bounds"),
+        }
+    }
+
+    /// Remove all elements from the HybridVec. A spilled HybridVec remains on the heap.
+    #[inline]
+    pub fn clear(&mut self) {
+        match self.data {
+            Inline(ref mut stack) => stack.clear(),
+            Spilled(ref mut vec) => vec.clear(),
+        }
+    }
+
+}
+
+
+#[test]
+fn spill_and_shrink() {
+    use N4;
+
+    let mut vec: HybridVec<uint, N4<uint>> = HybridVec::new();
+    for i in range(0u, 4) {
+        vec.push(i);
+    }
+    assert!(!vec.spilled());
+    vec.push(4);
+    assert!(vec.spilled());
+    assert_eq!(vec.iter().map(|x| *x).collect::<Vec<uint>>(), vec![0, 1, 2, 3, 4]);
+
+    assert!(!vec.shrink_to_inline());
+    assert_eq!(vec.remove(0), 0);
+    assert!(vec.shrink_to_inline());
+    assert!(!vec.spilled());
+    assert_eq!(vec.iter().map(|x| *x).collect::<Vec<uint>>(), vec![1, 2, 3, 4]);
+    assert_eq!(vec.pop(), 4);
+}
@@ -0,0 +1,152 @@
+//! Reading and writing bytes from and to byte StackVecs without allocating,
+//! e.g. for encoding OSC or MIDI SysEx messages.
+//!
+//! The `write_bytes` and `read_bytes` methods and `StackCursor` only use
+//! `core`. The `Writer`, `Reader`, `Buffer` and `Seek` implementations built
+//! upon them require the `std` feature, which is enabled by default.
+
+use core::cmp;
+#[cfg(feature = "std")]
+use std::io::{mod, Buffer, IoError, IoResult, Reader, Seek, SeekCur, SeekEnd, SeekSet,
+              SeekStyle, Writer};
+use {Length, StackVec, Storage};
+
+impl<A: Storage<u8>, L: Length> StackVec<A, L> {
+
+    /// Push as many of the given bytes as will fit, returning the number pushed.
+    pub fn write_bytes(&mut self, bytes: &[u8]) -> uint {
+        let n = cmp::min(bytes.len(), self.size() - self.len());
+        for byte in bytes.slice_to(n).iter() {
+            self.push(*byte);
+        }
+        n
+    }
+
+    /// Return a cursor for reading from the start of the bytes.
+    #[inline]
+    pub fn cursor<'a>(&'a self) -> StackCursor<'a> { StackCursor::new(self.as_slice()) }
+
+}
+
+#[cfg(feature = "std")]
+impl<A: Storage<u8>, L: Length> Writer for StackVec<A, L> {
+    /// Writes as many bytes as will fit. If the StackVec fills up before all
+    /// bytes are written, returns a `ShortWrite` error with the number written.
+    fn write(&mut self, buf: &[u8]) -> IoResult<()> {
+        let n = self.write_bytes(buf);
+        if n == buf.len() {
+            Ok(())
+        } else {
+            Err(IoError {
+                kind: io::ShortWrite(n),
+                desc: "StackVec capacity exceeded",
+                detail: None,
+            })
+        }
+    }
+}
+
+/// A cursor for reading through a slice of bytes, such as the contents of a byte StackVec.
+pub struct StackCursor<'a> {
+    bytes: &'a [u8],
+    pos: uint,
+}
+
+impl<'a> StackCursor<'a> {
+
+    /// Create a cursor at the start of the given bytes.
+    #[inline]
+    pub fn new(bytes: &'a [u8]) -> StackCursor<'a> {
+        StackCursor { bytes: bytes, pos: 0 }
+    }
+
+    /// Return the current position.
+    #[inline]
+    pub fn position(&self) -> uint { self.pos }
+
+    /// Return the bytes that have not yet been read.
+    #[inline]
+    pub fn remaining(&self) -> &'a [u8] {
+        self.bytes.slice_from(cmp::min(self.pos, self.bytes.len()))
+    }
+
+    /// Copy as many of the remaining bytes into `buf` as will fit, returning the number copied.
+    pub fn read_bytes(&mut self, buf: &mut [u8]) -> uint {
+        let remaining = self.remaining();
+        let n = cmp::min(buf.len(), remaining.len());
+        for (dst, src) in buf.iter_mut().zip(remaining.iter()) {
+            *dst = *src;
+        }
+        self.pos += n;
+        n
+    }
+
+}
+
+#[cfg(feature = "std")]
+impl<'a> Reader for StackCursor<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> IoResult<uint> {
+        if self.remaining().is_empty() {
+            return Err(io::standard_error(io::EndOfFile));
+        }
+        Ok(self.read_bytes(buf))
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a> Buffer for StackCursor<'a> {
+    fn fill_buf<'b>(&'b mut self) -> IoResult<&'b [u8]> {
+        if self.remaining().is_empty() {
+            Err(io::standard_error(io::EndOfFile))
+        } else {
+            Ok(self.remaining())
+        }
+    }
+
+    fn consume(&mut self, amt: uint) { self.pos += amt; }
+}
+
+#[cfg(feature = "std")]
+impl<'a> Seek for StackCursor<'a> {
+    fn tell(&self) -> IoResult<u64> { Ok(self.pos as u64) }
+
+    fn seek(&mut self, pos: i64, style: SeekStyle) -> IoResult<()> {
+        let base = match style {
+            SeekSet => 0,
+            SeekCur => self.pos as i64,
+            SeekEnd => self.bytes.len() as i64,
+        };
+        if base + pos < 0 {
+            return Err(IoError {
+                kind: io::InvalidInput,
+                desc: "seek to a negative position",
+                detail: None,
+            });
+        }
+        self.pos = (base + pos) as uint;
+        Ok(())
+    }
+}
+
+
+#[test]
+#[cfg(feature = "std")]
+fn write_then_read_back() {
+    use N8;
+
+    let mut msg: StackVec<N8<u8>> = StackVec::new();
+    msg.write(b"/osc").unwrap();
+    match msg.write(b"/note") {
+        Err(IoError { kind: io::ShortWrite(4), .. }) => (),
+        other => fail!("expected a short write, found {}", other),
+    }
+    assert_eq!(msg.as_slice(), b"/osc/not");
+
+    let mut cursor = msg.cursor();
+    let mut buf = [0u8, ..4];
+    assert_eq!(cursor.read(buf.as_mut_slice()), Ok(4));
+    assert_eq!(buf.as_slice(), b"/osc");
+    cursor.seek(-2, SeekEnd).unwrap();
+    assert_eq!(cursor.read_to_end().unwrap(), b"ot".to_vec());
+    assert!(cursor.read_byte().is_err());
+}
@@ -1,125 +1,324 @@
-
 #![crate_name = "stack_vec"]
 #![deny(missing_doc)]
-#![feature(macro_rules)]
+#![feature(default_type_params, macro_rules, simd, unsafe_destructor)]
 
 //! A small library for a stack-based Vec.
 
+extern crate core;
+#[cfg(all(test, not(any(windows, target_os = "android"))))]
+extern crate libc;
+
+pub use aligned::AlignedStackVec;
+pub use arena::StackArena;
+pub use bit_set::StackBitSet;
+pub use frames::StackFrames;
+pub use grid::StackGrid;
+pub use hash_map::StackHashMap;
+pub use hash_set::StackHashSet;
+pub use hybrid::HybridVec;
+pub use io::StackCursor;
+pub use list::StackList;
+pub use map::StackMap;
+#[cfg(feature = "pod")]
+pub use pod::PodStackVec;
+pub use pool::{PoolSlot, StackPool};
+pub use set::StackSet;
+pub use slice_vec::{SliceStorage, SliceVec};
+pub use sorted::{SortedStackVec, SortedStackVecByKey};
+pub use sparse::SparseStackVec;
+pub use storage::{Array, ArrayStorage, Length, Storage};
+pub use string::{Overflow, Reject, StackString, Truncate};
+
+use std::kinds::marker;
+use std::mem;
+use std::ptr;
+use std::raw;
+use std::slice;
+
 #[macro_escape]
 mod macros;
 
+#[cfg(all(test, not(any(windows, target_os = "android"))))]
+mod alloc_check;
+#[cfg(all(test, not(any(windows, target_os = "android"))))]
+mod no_alloc;
+
+pub mod aligned;
+pub mod algo;
+pub mod arena;
+pub mod bit_set;
+pub mod frames;
+pub mod grid;
+pub mod hash_map;
+pub mod hash_set;
+pub mod hybrid;
+pub mod io;
+pub mod list;
+pub mod map;
+#[cfg(feature = "num")]
+pub mod num;
+#[cfg(feature = "pod")]
+pub mod pod;
+pub mod pool;
+pub mod set;
+pub mod slice_vec;
+pub mod sorted;
+pub mod sparse;
+pub mod storage;
+pub mod string;
+pub mod wire;
+
 /// A stack-based vector for fast allocation.
-pub struct StackVec<A> {
-    len: uint,
+///
+/// The elements live within the StackVec's `Storage`, which by default is one
+/// of the fixed-size `Array`s aliased by `N2` to `N1024`. The length is stored
+/// as a `uint` unless a smaller `Length` type is given, e.g. `StackVec<N8<T>, u8>`.
+pub struct StackVec<A, L = uint> {
+    len: L,
     data: A,
 }
 
-impl<T, A: StackVecArray<T>> StackVec<A> {
+impl<T, A: ArrayStorage<T>, L: Length> StackVec<A, L> {
 
     /// Create an empty StackVec.
     #[inline]
-    pub fn new() -> StackVec<A> {
-        StackVec { len: 0u, data: StackVecArray::new(), }
+    pub fn new() -> StackVec<A, L> {
+        StackVec::from_storage(ArrayStorage::new())
+    }
+
+}
+
+impl<T, A: Storage<T>, L: Length> StackVec<A, L> {
+
+    /// Create an empty StackVec over the given storage.
+    ///
+    /// Any values within the storage's slots are ignored and will not be dropped.
+    /// Fails if the capacity of the storage exceeds the greatest value of the length type.
+    #[inline]
+    pub fn from_storage(data: A) -> StackVec<A, L> {
+        assert!(data.capacity() <= Length::max(None::<L>),
+                "StackVec::from_storage: capacity exceeds the length type");
+        StackVec { len: Length::from_uint(0), data: data, }
     }
 
+    /// Return a reference to the underlying storage.
+    #[inline]
+    pub fn storage(&self) -> &A { &self.data }
+
+    /// Set the number of occupied elems.
+    #[inline]
+    fn set_len(&mut self, len: uint) { self.len = Length::from_uint(len); }
+
     /// Push an element onto the end of the StackVec.
     #[inline]
     pub fn push(&mut self, elem: T) {
-        let idx = self.len;
-        self.len += 1;
-        self.data.set(idx, elem);
+        let len = self.len();
+        assert!(len < self.data.capacity(), "StackVec::push: capacity exceeded");
+        unsafe { ptr::write(self.data.as_mut_ptr().offset(len as int), elem) }
+        self.set_len(len + 1);
     }
 
     /// Remove and return the final element.
     #[inline]
     pub fn pop(&mut self) -> T {
-        let idx = self.len - 1u;
-        self.len -= 1;
-        self.data.remove(idx)
+        let len = self.len();
+        assert!(len > 0, "StackVec::pop: the StackVec is empty");
+        self.set_len(len - 1);
+        unsafe { ptr::read(self.data.as_ptr().offset(len as int - 1)) }
     }
 
     /// Return the number of occupied elems in the StackVec.
     #[inline]
-    pub fn len(&self) -> uint { self.len }
+    pub fn len(&self) -> uint { self.len.to_uint() }
 
-    /// Return an iterator over the elements.
+    /// Return the occupied elements as a slice.
+    #[inline]
+    pub fn as_slice<'a>(&'a self) -> &'a [T] {
+        unsafe { mem::transmute(raw::Slice { data: self.data.as_ptr(), len: self.len() }) }
+    }
+
+    /// Return the occupied elements as a mutable slice.
     #[inline]
-    pub fn iter<'a>(&'a self) -> Items<'a, T, A> {
-        Items { data: &self.data, len: self.len(), count: 0u }
+    pub fn as_mut_slice<'a>(&'a mut self) -> &'a mut [T] {
+        let len = self.len();
+        unsafe {
+            mem::transmute(raw::Slice { data: self.data.as_mut_ptr() as *const T, len: len })
+        }
     }
 
+    /// Return an iterator over the elements.
+    #[inline]
+    pub fn iter<'a>(&'a self) -> Items<'a, T> { self.as_slice().iter() }
+
+    /// Return an iterator over the elements that allows modifying each value.
+    #[inline]
+    pub fn iter_mut<'a>(&'a mut self) -> MutItems<'a, T> { self.as_mut_slice().iter_mut() }
+
     /// Return an immutable reference to the value at the given index.
     #[inline]
-    pub fn get(&self, idx: uint) -> &T { self.data.get(idx) }
+    pub fn get(&self, idx: uint) -> &T { &self.as_slice()[idx] }
 
     /// Return a mutable reference to the value at the given index.
     #[inline]
-    pub fn get_mut(&mut self, idx: uint) -> &mut T { self.data.get_mut(idx) }
+    pub fn get_mut(&mut self, idx: uint) -> &mut T { &mut self.as_mut_slice()[idx] }
 
-    /// Set the given index with the given element.
+    /// Set the given index with the given element, dropping the element it replaces.
     #[inline]
-    pub fn set(&mut self, idx: uint, elem: T) { self.data.set(idx, elem) }
+    pub fn set(&mut self, idx: uint, elem: T) { *self.get_mut(idx) = elem; }
+
+    /// Remove the element at the given index, shifting all following elements to the left.
+    ///
+    /// To keep elements at fixed indices, use a `SparseStackVec` instead.
+    pub fn remove(&mut self, idx: uint) -> T {
+        let len = self.len();
+        assert!(idx < len, "StackVec::remove: index out of bounds");
+        unsafe {
+            let ptr = self.data.as_mut_ptr().offset(idx as int);
+            let elem = ptr::read(ptr as *const T);
+            ptr::copy_memory(ptr, ptr.offset(1) as *const T, len - idx - 1);
+            self.set_len(len - 1);
+            elem
+        }
+    }
 
-    /// Remove an element from the given index and return it.
+    /// Return the length of the DspBuffer.
     #[inline]
-    pub fn remove(&mut self, idx: uint) -> T { self.data.remove(idx) }
+    pub fn size(&self) -> uint { self.data.capacity() }
 
-    /// Return the length of the DspBuffer.
+    /// Return true if the StackVec contains no elements.
     #[inline]
-    pub fn size(&self) -> uint { self.data.size() }
+    pub fn is_empty(&self) -> bool { self.len() == 0 }
 
-}
+    /// Return true if the StackVec has no free capacity left.
+    #[inline]
+    pub fn is_full(&self) -> bool { self.len() == self.data.capacity() }
+
+    /// Insert an element at the given index, shifting all following elements to the right.
+    pub fn insert(&mut self, idx: uint, elem: T) {
+        let len = self.len();
+        assert!(idx <= len, "StackVec::insert: index out of bounds");
+        assert!(len < self.data.capacity(), "StackVec::insert: capacity exceeded");
+        unsafe {
+            let ptr = self.data.as_mut_ptr().offset(idx as int);
+            ptr::copy_memory(ptr.offset(1), ptr as *const T, len - idx);
+            ptr::write(ptr, elem);
+        }
+        self.set_len(len + 1);
+    }
 
-/// A struct for iterating over StackVec's elements.
-pub struct Items<'a, T, A: 'a> {
-    data: &'a A,
-    len: uint,
-    count: uint,
-}
+    /// Push an element onto the end of the StackVec, or hand it back as `Err` if the StackVec is full.
+    #[inline]
+    pub fn try_push(&mut self, elem: T) -> Result<(), T> {
+        if self.is_full() {
+            return Err(elem);
+        }
+        self.push(elem);
+        Ok(())
+    }
+
+    /// Shorten the StackVec to the given length, dropping the elements beyond it.
+    #[inline]
+    pub fn truncate(&mut self, len: uint) {
+        while self.len() > len {
+            self.pop();
+        }
+    }
+
+    /// Retain only the elements for which the predicate returns true, preserving their order.
+    pub fn retain(&mut self, f: |&T| -> bool) {
+        let mut kept = 0;
+        {
+            let v = self.as_mut_slice();
+            for i in range(0, v.len()) {
+                if f(&v[i]) {
+                    v.swap(kept, i);
+                    kept += 1;
+                }
+            }
+        }
+        self.truncate(kept);
+    }
 
-impl<'a, T, A: StackVecArray<T> + 'a> Iterator<&'a T> for Items<'a, T, A> {
+    /// Remove all elements from the StackVec.
     #[inline]
-    fn next(&mut self) -> Option<&'a T> {
-        if self.count < self.len {
-            let count = self.count;
-            self.count += 1;
-            Some(self.data.get(count))
-        } else { None }
+    pub fn clear(&mut self) { self.truncate(0); }
+
+    /// Empty the StackVec, returning an iterator that moves its elements out
+    /// from front to back.
+    ///
+    /// Any elements the iterator does not yield are dropped along with it.
+    pub fn drain<'a>(&'a mut self) -> Drain<'a, T> {
+        let len = self.len();
+        self.set_len(0);
+        Drain {
+            ptr: self.data.as_mut_ptr(),
+            start: 0,
+            end: len,
+            marker: marker::ContravariantLifetime,
+        }
     }
+
 }
 
-/*
+#[unsafe_destructor]
+impl<T, A: Storage<T>, L: Length> Drop for StackVec<A, L> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+/// A struct for iterating over StackVec's elements.
+pub type Items<'a, T> = slice::Items<'a, T>;
+
 /// A struct for iterating over StackVec's elements mutably.
-pub struct MutItems<'a, T: 'a, A: 'a> {
-    data: &'a mut A,
-    len: uint,
-    count: uint,
+pub type MutItems<'a, T> = slice::MutItems<'a, T>;
+
+/// An iterator that moves the elements out of a StackVec, as returned by `drain`.
+pub struct Drain<'a, T> {
+    ptr: *mut T,
+    start: uint,
+    end: uint,
+    marker: marker::ContravariantLifetime<'a>,
 }
 
-impl<'a, T: 'a, A: StackVecArray<T> + 'a> Iterator<&'a mut T> for MutItems<'a, T, A> {
+impl<'a, T> Iterator<T> for Drain<'a, T> {
+    #[inline]
+    fn next(&mut self) -> Option<T> {
+        if self.start == self.end { return None }
+        let idx = self.start;
+        self.start += 1;
+        Some(unsafe { ptr::read(self.ptr.offset(idx as int) as *const T) })
+    }
     #[inline]
-    fn next(&mut self) -> Option<&'a mut T> {
-        if self.count < self.len {
-            let count = self.count;
-            self.count += 1;
-            Some(self.data.get_mut(count))
-        } else { None }
+    fn size_hint(&self) -> (uint, Option<uint>) {
+        let remaining = self.end - self.start;
+        (remaining, Some(remaining))
     }
 }
-*/
 
+impl<'a, T> DoubleEndedIterator<T> for Drain<'a, T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<T> {
+        if self.start == self.end { return None }
+        self.end -= 1;
+        Some(unsafe { ptr::read(self.ptr.offset(self.end as int) as *const T) })
+    }
+}
 
-///// A struct for iterating over a StackVec's elements mutably.
-//pub struct MutItems<'a, T: 'a> {
-//    ptr: *mut T,
-//    end: *mut T,
-//    marker: marker::ContravariantLifetime<'a>,
-//    marker2: marker::NoCopy,
-//}
+#[unsafe_destructor]
+impl<'a, T> Drop for Drain<'a, T> {
+    fn drop(&mut self) {
+        while self.next().is_some() {}
+    }
+}
 
 
-/// A trait to be implemented for all fixed-size arrays that
+/// A trait to be implemented for all fixed-size arrays of optional slots that
 /// are power of 2 up to size 1024.
+///
+/// Unlike the `Storage` behind a StackVec, each slot may be individually
+/// filled or emptied, which the slot-based collections such as the
+/// StackHashMap and StackList rely upon.
 pub trait StackVecArray<T> {
     /// Constructor for a StackVecArray.
     fn new() -> Self;
@@ -131,37 +330,46 @@ pub trait StackVecArray<T> {
     fn set(&mut self, idx: uint, elem: T);
     /// Remove an element from the given index and return it.
     fn remove(&mut self, idx: uint) -> T;
+    /// Return true if the given index currently holds an element.
+    fn is_occupied(&self, idx: uint) -> bool;
     /// Return the length of the DspBuffer.
     fn size(&self) -> uint;
 }
 
-/*
-impl<T, A: StackVecArray<T>> StackVecArray<T> for StackVec<A> {
-    #[inline]
-    fn new() -> StackVec<A> { StackVec::new() }
-    #[inline]
-    fn get(&self, idx: uint) -> &T { self.data.get(idx) }
-    #[inline]
-    fn get_mut(&mut self, idx: uint) -> &mut T { self.data.get_mut(idx) }
-    #[inline]
-    fn set(&mut self, idx: uint, elem: T) { self.data.set(idx, elem) }
-    #[inline]
-    fn remove(&mut self, idx: uint) -> T { self.data.remove(idx) }
-    #[inline]
-    fn size(&self) -> uint { self.data.size() }
-}
-*/
-
-pub type N2<T> = [Option<T>, ..2];
-pub type N4<T> = [Option<T>, ..4];
-pub type N8<T> = [Option<T>, ..8];
-pub type N16<T> = [Option<T>, ..16];
-pub type N32<T> = [Option<T>, ..32];
-pub type N64<T> = [Option<T>, ..64];
-pub type N128<T> = [Option<T>, ..128];
-pub type N256<T> = [Option<T>, ..256];
-pub type N512<T> = [Option<T>, ..512];
-pub type N1024<T> = [Option<T>, ..1024];
+pub type N2<T> = Array<[T, ..2]>;
+pub type N4<T> = Array<[T, ..4]>;
+pub type N8<T> = Array<[T, ..8]>;
+pub type N16<T> = Array<[T, ..16]>;
+pub type N32<T> = Array<[T, ..32]>;
+pub type N64<T> = Array<[T, ..64]>;
+pub type N128<T> = Array<[T, ..128]>;
+pub type N256<T> = Array<[T, ..256]>;
+pub type N512<T> = Array<[T, ..512]>;
+pub type N1024<T> = Array<[T, ..1024]>;
+
+/// StackVecs whose length is stored in the smallest type able to hold their
+/// capacity, e.g. `Compact64<f32>` for `StackVec<N64<f32>, u8>`.
+pub type Compact2<T> = StackVec<N2<T>, u8>;
+pub type Compact4<T> = StackVec<N4<T>, u8>;
+pub type Compact8<T> = StackVec<N8<T>, u8>;
+pub type Compact16<T> = StackVec<N16<T>, u8>;
+pub type Compact32<T> = StackVec<N32<T>, u8>;
+pub type Compact64<T> = StackVec<N64<T>, u8>;
+pub type Compact128<T> = StackVec<N128<T>, u8>;
+pub type Compact256<T> = StackVec<N256<T>, u16>;
+pub type Compact512<T> = StackVec<N512<T>, u16>;
+pub type Compact1024<T> = StackVec<N1024<T>, u16>;
+
+pub type Slots2<T> = [Option<T>, ..2];
+pub type Slots4<T> = [Option<T>, ..4];
+pub type Slots8<T> = [Option<T>, ..8];
+pub type Slots16<T> = [Option<T>, ..16];
+pub type Slots32<T> = [Option<T>, ..32];
+pub type Slots64<T> = [Option<T>, ..64];
+pub type Slots128<T> = [Option<T>, ..128];
+pub type Slots256<T> = [Option<T>, ..256];
+pub type Slots512<T> = [Option<T>, ..512];
+pub type Slots1024<T> = [Option<T>, ..1024];
 
 
 impl<T> StackVecArray<T> for [Option<T>, ..2] {
@@ -524,3 +732,58 @@ fn it_works() {
     }
 
 }
+
+#[test]
+fn drops_only_occupied_elements() {
+    use std::cell::Cell;
+
+    struct Counted<'a> { drops: &'a Cell<uint> }
+
+    #[unsafe_destructor]
+    impl<'a> Drop for Counted<'a> {
+        fn drop(&mut self) { self.drops.set(self.drops.get() + 1); }
+    }
+
+    let drops = Cell::new(0u);
+    {
+        let mut vec: StackVec<N8<Counted>> = StackVec::new();
+        for _ in range(0u, 5) {
+            vec.push(Counted { drops: &drops });
+        }
+        vec.insert(2, Counted { drops: &drops });
+        vec.remove(0);
+        assert_eq!(drops.get(), 1);
+        assert_eq!(vec.len(), 5);
+    }
+    assert_eq!(drops.get(), 6);
+}
+
+#[test]
+fn drain_moves_out_every_element() {
+    let mut vec: StackVec<N8<String>> = StackVec::new();
+    for name in ["kick", "snare", "hat", "ride"].iter() {
+        vec.push(name.to_string());
+    }
+    {
+        let mut drain = vec.drain();
+        assert_eq!(drain.size_hint(), (4, Some(4)));
+        assert_eq!(drain.next().unwrap().as_slice(), "kick");
+        assert_eq!(drain.next_back().unwrap().as_slice(), "ride");
+    }
+    assert!(vec.is_empty());
+    vec.push("tom".to_string());
+    assert_eq!(vec.drain().collect::<Vec<String>>(), vec!["tom".to_string()]);
+}
+
+#[test]
+fn small_length_types_shrink_the_header() {
+    let mut small: Compact8<u8> = StackVec::new();
+    for i in range(0u8, 8) {
+        small.push(i);
+    }
+    assert_eq!(small.remove(0), 0);
+    assert_eq!(small.len(), 7);
+
+    assert!(mem::size_of::<Compact8<u8>>() < mem::size_of::<StackVec<N8<u8>>>());
+    assert!(mem::size_of::<Compact256<u8>>() < mem::size_of::<StackVec<N256<u8>>>());
+}
@@ -0,0 +1,464 @@
+//! A doubly-linked list whose nodes live within fixed-size stack storage.
+
+use std::uint;
+use StackVecArray;
+
+/// Marks the absence of a linked node.
+static NIL: uint = uint::MAX;
+
+/// A single node of a StackList.
+pub struct Node<T> {
+    elem: Option<T>,
+    prev: uint,
+    next: uint,
+    /// Bumped each time the node is vacated, so that stale handles can be told apart.
+    generation: uint,
+}
+
+/// A stable reference to an element within a StackList.
+///
+/// A Handle remains valid across any other insertions, removals and moves
+/// until the element it refers to is itself removed from the list. Each
+/// Handle records the generation of its node, so a stale Handle is never
+/// mistaken for a later element that reuses the same node.
+#[deriving(Clone, PartialEq, Eq, Show)]
+pub struct Handle(uint, uint);
+
+/// A doubly-linked list that links its nodes by index within a fixed-size array.
+///
+/// Elements never move once inserted, so removing or relinking an element
+/// anywhere within the list is O(1), which makes it a good fit for LRU orderings.
+pub struct StackList<A> {
+    nodes: A,
+    len: uint,
+    head: uint,
+    tail: uint,
+    /// The first of the vacated nodes, linked via their `next` index.
+    free: uint,
+    /// The number of nodes that have ever been used.
+    used: uint,
+}
+
+impl<T, A: StackVecArray<Node<T>>> StackList<A> {
+
+    /// Create an empty StackList.
+    #[inline]
+    pub fn new() -> StackList<A> {
+        StackList {
+            nodes: StackVecArray::new(),
+            len: 0,
+            head: NIL,
+            tail: NIL,
+            free: NIL,
+            used: 0,
+        }
+    }
+
+    /// Return the number of elements within the StackList.
+    #[inline]
+    pub fn len(&self) -> uint { self.len }
+
+    /// Return true if the StackList contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.len == 0 }
+
+    /// Return true if no more elements can be inserted.
+    #[inline]
+    pub fn is_full(&self) -> bool { self.len == self.nodes.size() }
+
+    /// Return the maximum number of elements the StackList can hold.
+    #[inline]
+    pub fn capacity(&self) -> uint { self.nodes.size() }
+
+    /// Return the index of the node the given handle refers to, if its element is still present.
+    #[inline]
+    fn index_of(&self, handle: Handle) -> Option<uint> {
+        let Handle(idx, generation) = handle;
+        if idx < self.used && self.nodes.get(idx).generation == generation
+        && self.nodes.get(idx).elem.is_some() {
+            Some(idx)
+        } else {
+            None
+        }
+    }
+
+    /// Return the index of the node the given handle refers to, failing if the handle is stale.
+    #[inline]
+    fn live_index(&self, handle: Handle) -> uint {
+        match self.index_of(handle) {
+            Some(idx) => idx,
+            None => fail!("StackList: stale handle"),
+        }
+    }
+
+    /// Return a handle to the node at the given index.
+    #[inline]
+    fn handle_of(&self, idx: uint) -> Handle {
+        Handle(idx, self.nodes.get(idx).generation)
+    }
+
+    /// Claim a node for the given element, handing the element back if the list is full.
+    fn alloc(&mut self, elem: T) -> Result<uint, T> {
+        if self.free != NIL {
+            let idx = self.free;
+            let node = self.nodes.get_mut(idx);
+            self.free = node.next;
+            node.elem = Some(elem);
+            Ok(idx)
+        } else if self.used < self.nodes.size() {
+            let idx = self.used;
+            self.used += 1;
+            self.nodes.set(idx, Node { elem: Some(elem), prev: NIL, next: NIL, generation: 0 });
+            Ok(idx)
+        } else {
+            Err(elem)
+        }
+    }
+
+    /// Link the node at `idx` between the nodes at `prev` and `next`.
+    fn link(&mut self, idx: uint, prev: uint, next: uint) {
+        {
+            let node = self.nodes.get_mut(idx);
+            node.prev = prev;
+            node.next = next;
+        }
+        if prev == NIL { self.head = idx } else { self.nodes.get_mut(prev).next = idx }
+        if next == NIL { self.tail = idx } else { self.nodes.get_mut(next).prev = idx }
+        self.len += 1;
+    }
+
+    /// Unlink the node at `idx` from its neighbours.
+    fn unlink(&mut self, idx: uint) {
+        let (prev, next) = {
+            let node = self.nodes.get(idx);
+            (node.prev, node.next)
+        };
+        if prev == NIL { self.head = next } else { self.nodes.get_mut(prev).next = next }
+        if next == NIL { self.tail = prev } else { self.nodes.get_mut(next).prev = prev }
+        self.len -= 1;
+    }
+
+    /// Unlink the node at `idx`, returning its element and adding the node to the free list.
+    fn release(&mut self, idx: uint) -> T {
+        self.unlink(idx);
+        let node = self.nodes.get_mut(idx);
+        node.next = self.free;
+        node.generation += 1;
+        self.free = idx;
+        node.elem.take().unwrap()
+    }
+
+    /// Insert the element between the nodes at `prev` and `next`.
+    fn try_insert_between(&mut self, elem: T, prev: uint, next: uint) -> Result<Handle, T> {
+        let idx = try!(self.alloc(elem));
+        self.link(idx, prev, next);
+        Ok(self.handle_of(idx))
+    }
+
+    /// Insert the element between the nodes at `prev` and `next`, failing if the list is full.
+    fn insert_between(&mut self, elem: T, prev: uint, next: uint) -> Handle {
+        match self.try_insert_between(elem, prev, next) {
+            Ok(handle) => handle,
+            Err(_) => fail!("StackList: capacity of {} exceeded", self.capacity()),
+        }
+    }
+
+    /// Push an element onto the front of the StackList, returning its handle.
+    ///
+    /// Fails if the StackList is already full.
+    #[inline]
+    pub fn push_front(&mut self, elem: T) -> Handle {
+        let head = self.head;
+        self.insert_between(elem, NIL, head)
+    }
+
+    /// Push an element onto the back of the StackList, returning its handle.
+    ///
+    /// Fails if the StackList is already full.
+    #[inline]
+    pub fn push_back(&mut self, elem: T) -> Handle {
+        let tail = self.tail;
+        self.insert_between(elem, tail, NIL)
+    }
+
+    /// Push an element onto the front of the StackList, handing it back if the list is full.
+    #[inline]
+    pub fn try_push_front(&mut self, elem: T) -> Result<Handle, T> {
+        let head = self.head;
+        self.try_insert_between(elem, NIL, head)
+    }
+
+    /// Push an element onto the back of the StackList, handing it back if the list is full.
+    #[inline]
+    pub fn try_push_back(&mut self, elem: T) -> Result<Handle, T> {
+        let tail = self.tail;
+        self.try_insert_between(elem, tail, NIL)
+    }
+
+    /// Remove and return the first element.
+    pub fn pop_front(&mut self) -> Option<T> {
+        if self.head == NIL { return None }
+        let head = self.head;
+        Some(self.release(head))
+    }
+
+    /// Remove and return the last element.
+    pub fn pop_back(&mut self) -> Option<T> {
+        if self.tail == NIL { return None }
+        let tail = self.tail;
+        Some(self.release(tail))
+    }
+
+    /// Return the handle of the first element.
+    #[inline]
+    pub fn front_handle(&self) -> Option<Handle> {
+        if self.head == NIL { None } else { Some(self.handle_of(self.head)) }
+    }
+
+    /// Return the handle of the last element.
+    #[inline]
+    pub fn back_handle(&self) -> Option<Handle> {
+        if self.tail == NIL { None } else { Some(self.handle_of(self.tail)) }
+    }
+
+    /// Return an immutable reference to the first element.
+    #[inline]
+    pub fn front(&self) -> Option<&T> {
+        if self.head == NIL { None } else { self.nodes.get(self.head).elem.as_ref() }
+    }
+
+    /// Return an immutable reference to the last element.
+    #[inline]
+    pub fn back(&self) -> Option<&T> {
+        if self.tail == NIL { None } else { self.nodes.get(self.tail).elem.as_ref() }
+    }
+
+    /// Return an immutable reference to the element with the given handle.
+    #[inline]
+    pub fn get(&self, handle: Handle) -> Option<&T> {
+        match self.index_of(handle) {
+            Some(idx) => self.nodes.get(idx).elem.as_ref(),
+            None => None,
+        }
+    }
+
+    /// Return a mutable reference to the element with the given handle.
+    #[inline]
+    pub fn get_mut(&mut self, handle: Handle) -> Option<&mut T> {
+        match self.index_of(handle) {
+            Some(idx) => self.nodes.get_mut(idx).elem.as_mut(),
+            None => None,
+        }
+    }
+
+    /// Remove the element with the given handle and return it.
+    pub fn remove(&mut self, handle: Handle) -> Option<T> {
+        match self.index_of(handle) {
+            Some(idx) => Some(self.release(idx)),
+            None => None,
+        }
+    }
+
+    /// Insert an element before the element with the given handle.
+    ///
+    /// Fails if the handle is stale or the StackList is already full.
+    pub fn insert_before(&mut self, handle: Handle, elem: T) -> Handle {
+        let idx = self.live_index(handle);
+        let prev = self.nodes.get(idx).prev;
+        self.insert_between(elem, prev, idx)
+    }
+
+    /// Insert an element after the element with the given handle.
+    ///
+    /// Fails if the handle is stale or the StackList is already full.
+    pub fn insert_after(&mut self, handle: Handle, elem: T) -> Handle {
+        let idx = self.live_index(handle);
+        let next = self.nodes.get(idx).next;
+        self.insert_between(elem, idx, next)
+    }
+
+    /// Move the element with the given handle to the front of the StackList.
+    pub fn move_to_front(&mut self, handle: Handle) {
+        let idx = self.live_index(handle);
+        if self.head != idx {
+            self.unlink(idx);
+            let head = self.head;
+            self.link(idx, NIL, head);
+        }
+    }
+
+    /// Move the element with the given handle to the back of the StackList.
+    pub fn move_to_back(&mut self, handle: Handle) {
+        let idx = self.live_index(handle);
+        if self.tail != idx {
+            self.unlink(idx);
+            let tail = self.tail;
+            self.link(idx, tail, NIL);
+        }
+    }
+
+    /// Remove all elements from the StackList.
+    pub fn clear(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+
+    /// Return an iterator over the elements from front to back.
+    #[inline]
+    pub fn iter<'a>(&'a self) -> Items<'a, T, A> {
+        Items { nodes: &self.nodes, idx: self.head, remaining: self.len }
+    }
+
+    /// Return a cursor positioned at the first element.
+    #[inline]
+    pub fn cursor_front<'a>(&'a mut self) -> Cursor<'a, T, A> {
+        let head = self.head;
+        Cursor { list: self, idx: head }
+    }
+
+    /// Return a cursor positioned at the last element.
+    #[inline]
+    pub fn cursor_back<'a>(&'a mut self) -> Cursor<'a, T, A> {
+        let tail = self.tail;
+        Cursor { list: self, idx: tail }
+    }
+
+    /// Return a cursor positioned at the element with the given handle.
+    ///
+    /// Fails if the handle is stale.
+    #[inline]
+    pub fn cursor<'a>(&'a mut self, handle: Handle) -> Cursor<'a, T, A> {
+        let idx = self.live_index(handle);
+        Cursor { list: self, idx: idx }
+    }
+
+}
+
+/// A struct for iterating over a StackList's elements from front to back.
+pub struct Items<'a, T, A: 'a> {
+    nodes: &'a A,
+    idx: uint,
+    remaining: uint,
+}
+
+impl<'a, T, A: StackVecArray<Node<T>> + 'a> Iterator<&'a T> for Items<'a, T, A> {
+    #[inline]
+    fn next(&mut self) -> Option<&'a T> {
+        if self.idx == NIL { return None }
+        let node = self.nodes.get(self.idx);
+        self.idx = node.next;
+        self.remaining -= 1;
+        node.elem.as_ref()
+    }
+    #[inline]
+    fn size_hint(&self) -> (uint, Option<uint>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+/// A cursor for walking and editing a StackList in place.
+///
+/// Besides pointing at an element, the cursor may point at the "ghost"
+/// position past either end of the list, which it reaches when it walks off
+/// the front or back.
+pub struct Cursor<'a, T: 'a, A: 'a> {
+    list: &'a mut StackList<A>,
+    idx: uint,
+}
+
+impl<'a, T, A: StackVecArray<Node<T>>> Cursor<'a, T, A> {
+
+    /// Return the handle of the current element.
+    #[inline]
+    pub fn handle(&self) -> Option<Handle> {
+        if self.idx == NIL { None } else { Some(self.list.handle_of(self.idx)) }
+    }
+
+    /// Return a mutable reference to the current element.
+    #[inline]
+    pub fn current(&mut self) -> Option<&mut T> {
+        if self.idx == NIL { None } else { self.list.nodes.get_mut(self.idx).elem.as_mut() }
+    }
+
+    /// Move to the next element, wrapping from the ghost position to the front.
+    #[inline]
+    pub fn move_next(&mut self) {
+        self.idx = if self.idx == NIL { self.list.head } else { self.list.nodes.get(self.idx).next };
+    }
+
+    /// Move to the previous element, wrapping from the ghost position to the back.
+    #[inline]
+    pub fn move_prev(&mut self) {
+        self.idx = if self.idx == NIL { self.list.tail } else { self.list.nodes.get(self.idx).prev };
+    }
+
+    /// Insert an element before the current one, or at the back when at the ghost position.
+    ///
+    /// Fails if the StackList is already full.
+    pub fn insert_before(&mut self, elem: T) -> Handle {
+        let next = self.idx;
+        let prev = if next == NIL { self.list.tail } else { self.list.nodes.get(next).prev };
+        self.list.insert_between(elem, prev, next)
+    }
+
+    /// Insert an element after the current one, or at the front when at the ghost position.
+    ///
+    /// Fails if the StackList is already full.
+    pub fn insert_after(&mut self, elem: T) -> Handle {
+        let prev = self.idx;
+        let next = if prev == NIL { self.list.head } else { self.list.nodes.get(prev).next };
+        self.list.insert_between(elem, prev, next)
+    }
+
+    /// Remove the current element and return it, moving the cursor on to the next element.
+    pub fn remove_current(&mut self) -> Option<T> {
+        if self.idx == NIL { return None }
+        let idx = self.idx;
+        self.idx = self.list.nodes.get(idx).next;
+        Some(self.list.release(idx))
+    }
+
+    /// Move the current element to the front of the list. The cursor stays with the element.
+    pub fn move_to_front(&mut self) {
+        if self.idx != NIL {
+            let handle = self.list.handle_of(self.idx);
+            self.list.move_to_front(handle);
+        }
+    }
+
+}
+
+
+#[test]
+fn lru_ordering() {
+    use Slots4;
+
+    let mut lru: StackList<Slots4<Node<&'static str>>> = StackList::new();
+    let a = lru.push_back("a");
+    let b = lru.push_back("b");
+    let c = lru.push_back("c");
+    lru.push_back("d");
+    assert_eq!(lru.try_push_back("e"), Err("e"));
+
+    lru.move_to_front(c);
+    assert_eq!(lru.remove(b), Some("b"));
+    assert_eq!(lru.remove(b), None);
+    lru.insert_after(a, "x");
+    assert_eq!(lru.iter().map(|s| *s).collect::<Vec<&str>>(), vec!["c", "a", "x", "d"]);
+
+    {
+        let mut cursor = lru.cursor(a);
+        assert_eq!(cursor.remove_current(), Some("a"));
+        assert_eq!(cursor.current().map(|s| *s), Some("x"));
+        cursor.move_to_front();
+    }
+    assert_eq!(lru.iter().map(|s| *s).collect::<Vec<&str>>(), vec!["x", "c", "d"]);
+    assert_eq!(lru.get(c), Some(&"c"));
+    assert_eq!(lru.pop_back(), Some("d"));
+
+    // "e" reuses the node vacated by "c", which the stale handle must not reach.
+    assert_eq!(lru.remove(c), Some("c"));
+    let e = lru.push_back("e");
+    assert_eq!(lru.get(c), None);
+    assert_eq!(lru.remove(c), None);
+    assert_eq!(lru.get(e), Some(&"e"));
+}
@@ -12,9 +12,159 @@ macro_rules! impl_stack_vec_array_basic_methods(
         #[inline]
         fn remove(&mut self, idx: uint) -> T { self[idx].take().unwrap() }
         #[inline]
+        fn is_occupied(&self, idx: uint) -> bool { self[idx].is_some() }
+        #[inline]
         fn size(&self) -> uint { $len }
     )
 )
 
+/// Implement the BitSetWords trait for a fixed-size array of words.
+macro_rules! impl_bit_set_words(
+    ($len:expr) => (
+        impl BitSetWords for [u64, ..$len] {
+            #[inline]
+            fn zeroed() -> [u64, ..$len] { [0u64, ..$len] }
+            #[inline]
+            fn words(&self) -> &[u64] { self.as_slice() }
+            #[inline]
+            fn words_mut(&mut self) -> &mut [u64] { self.as_mut_slice() }
+        }
+    )
+)
+
+/// Implement the Storage and ArrayStorage traits for an Array of the given length.
+macro_rules! impl_array_storage(
+    ($len:expr) => (
+        impl<T, M> Storage<T> for Array<[T, ..$len], M> {
+            #[inline]
+            fn as_ptr(&self) -> *const T { self.array().as_ptr() }
+            #[inline]
+            fn as_mut_ptr(&mut self) -> *mut T { self.array_mut().as_mut_ptr() }
+            #[inline]
+            fn capacity(&self) -> uint { $len }
+        }
+
+        impl<T, M> ArrayStorage<T> for Array<[T, ..$len], M> {
+            #[inline]
+            fn new() -> Array<[T, ..$len], M> { Array::uninitialized() }
+        }
+    )
+)
+
+/// Implement the element-wise arithmetic operators between a StackVec and a scalar.
+macro_rules! impl_scalar_ops(
+    ($t:ty) => (
+        impl<A: ArrayStorage<$t>, L: Length> Add<$t, StackVec<A, L>> for StackVec<A, L> {
+            #[inline]
+            fn add(&self, rhs: &$t) -> StackVec<A, L> { map_each(self, |a| a + *rhs) }
+        }
+
+        impl<A: ArrayStorage<$t>, L: Length> Sub<$t, StackVec<A, L>> for StackVec<A, L> {
+            #[inline]
+            fn sub(&self, rhs: &$t) -> StackVec<A, L> { map_each(self, |a| a - *rhs) }
+        }
+
+        impl<A: ArrayStorage<$t>, L: Length> Mul<$t, StackVec<A, L>> for StackVec<A, L> {
+            #[inline]
+            fn mul(&self, rhs: &$t) -> StackVec<A, L> { map_each(self, |a| a * *rhs) }
+        }
+
+        impl<A: ArrayStorage<$t>, L: Length> Div<$t, StackVec<A, L>> for StackVec<A, L> {
+            #[inline]
+            fn div(&self, rhs: &$t) -> StackVec<A, L> { map_each(self, |a| a / *rhs) }
+        }
+    )
+)
+
+/// Format the given arguments into a new `StackString` of the given size, without allocating.
+///
+/// The size is given as one of the array aliases, e.g. `N64`, and is followed by
+/// the `Overflow` policy to apply should the text not fit. Evaluates to a
+/// `Result<StackString<N<u8>>, fmt::WriteError>`.
+///
+/// ```ignore
+/// use stack_vec::Truncate;
+///
+/// let msg = stack_format!(N64, Truncate, "voice {} stolen", idx).unwrap();
+/// ```
+#[macro_export]
+macro_rules! stack_format(
+    ($size:ident, $overflow:expr, $($arg:tt)*) => ({
+        let result: Result<::stack_vec::string::StackString<::stack_vec::$size<u8>>,
+                           ::std::fmt::WriteError> =
+            format_args!(|args| ::stack_vec::string::StackString::format($overflow, args), $($arg)*);
+        result
+    })
+)
+
+/// Implement the Pod marker trait for a primitive type.
+macro_rules! impl_pod(
+    ($t:ty) => (
+        impl Pod for $t {}
+    )
+)
+
+/// Implement Pod for fixed-size arrays of Pod elements of the given length,
+/// along with the Storage and ArrayStorage traits so that they may back a StackVec.
+macro_rules! impl_pod_array(
+    ($len:expr) => (
+        impl<T: Pod> Pod for [T, ..$len] {}
 
+        impl<T: Pod> Storage<T> for [T, ..$len] {
+            #[inline]
+            fn as_ptr(&self) -> *const T { self.as_slice().as_ptr() }
+            #[inline]
+            fn as_mut_ptr(&mut self) -> *mut T { self.as_mut_slice().as_mut_ptr() }
+            #[inline]
+            fn capacity(&self) -> uint { $len }
+        }
 
+        impl<T: Pod> ArrayStorage<T> for [T, ..$len] {
+            #[inline]
+            fn new() -> [T, ..$len] { unsafe { mem::zeroed() } }
+        }
+    )
+)
+
+/// Implement the WireElem trait for a primitive integer type of the given width in bytes.
+macro_rules! impl_wire_elem_int(
+    ($t:ty, $bytes:expr) => (
+        impl WireElem for $t {
+            #[inline]
+            fn encode_le(&self, buf: &mut [u8]) -> Option<uint> {
+                if buf.len() < $bytes {
+                    return None;
+                }
+                for i in range(0u, $bytes) {
+                    buf[i] = (*self as u64 >> (8 * i)) as u8;
+                }
+                Some($bytes)
+            }
+            #[inline]
+            fn decode_le(buf: &[u8]) -> Option<($t, uint)> {
+                if buf.len() < $bytes {
+                    return None;
+                }
+                let mut bits = 0u64;
+                for i in range(0u, $bytes) {
+                    bits |= buf[i] as u64 << (8 * i);
+                }
+                Some((bits as $t, $bytes))
+            }
+        }
+    )
+)
+
+/// Implement the Length trait for an unsigned integer type.
+macro_rules! impl_length(
+    ($t:ident) => (
+        impl Length for $t {
+            #[inline]
+            fn from_uint(n: uint) -> $t { n as $t }
+            #[inline]
+            fn to_uint(self) -> uint { self as uint }
+            #[inline]
+            fn max(_: Option<$t>) -> uint { ::std::$t::MAX as uint }
+        }
+    )
+)
@@ -0,0 +1,320 @@
+//! A sorted, fixed-capacity associative map built on top of a StackVec of pairs.
+
+use std::mem;
+use {ArrayStorage, StackVec, Storage};
+
+/// A map that stores its key-value pairs sorted by key within a StackVec.
+///
+/// Lookups, insertions and removals use a binary search over the keys, which
+/// for small dictionaries beats hashing while never touching the heap.
+pub struct StackMap<A> {
+    vec: StackVec<A>,
+}
+
+impl<K: Ord, V, A: ArrayStorage<(K, V)>> StackMap<A> {
+
+    /// Create an empty StackMap.
+    #[inline]
+    pub fn new() -> StackMap<A> {
+        StackMap::from_storage(ArrayStorage::new())
+    }
+
+}
+
+impl<K: Ord, V, A: Storage<(K, V)>> StackMap<A> {
+
+    /// Create an empty StackMap over the given storage.
+    #[inline]
+    pub fn from_storage(storage: A) -> StackMap<A> {
+        StackMap { vec: StackVec::from_storage(storage) }
+    }
+
+    /// Return the number of key-value pairs within the StackMap.
+    #[inline]
+    pub fn len(&self) -> uint { self.vec.len() }
+
+    /// Return true if the StackMap contains no pairs.
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.vec.is_empty() }
+
+    /// Return true if no more pairs can be inserted.
+    #[inline]
+    pub fn is_full(&self) -> bool { self.vec.is_full() }
+
+    /// Return the maximum number of pairs the StackMap can hold.
+    #[inline]
+    pub fn capacity(&self) -> uint { self.vec.size() }
+
+    /// Binary search for the given key.
+    ///
+    /// Returns `Ok` with the index of the key if found, otherwise `Err` with
+    /// the index at which the key would be inserted.
+    fn search(&self, key: &K) -> Result<uint, uint> {
+        let (mut lo, mut hi) = (0u, self.vec.len());
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            let &(ref k, _) = self.vec.get(mid);
+            match k.cmp(key) {
+                Less => lo = mid + 1,
+                Greater => hi = mid,
+                Equal => return Ok(mid),
+            }
+        }
+        Err(lo)
+    }
+
+    /// Return the index of the first pair whose key is not less than the given key.
+    fn lower_bound(&self, key: &K) -> uint {
+        match self.search(key) {
+            Ok(idx) | Err(idx) => idx,
+        }
+    }
+
+    /// Return true if the StackMap contains the given key.
+    #[inline]
+    pub fn contains_key(&self, key: &K) -> bool { self.search(key).is_ok() }
+
+    /// Return an immutable reference to the value for the given key.
+    pub fn get(&self, key: &K) -> Option<&V> {
+        match self.search(key) {
+            Ok(idx) => {
+                let &(_, ref v) = self.vec.get(idx);
+                Some(v)
+            },
+            Err(_) => None,
+        }
+    }
+
+    /// Return a mutable reference to the value for the given key.
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        match self.search(key) {
+            Ok(idx) => match *self.vec.get_mut(idx) {
+                (_, ref mut v) => Some(v),
+            },
+            Err(_) => None,
+        }
+    }
+
+    /// Insert a key-value pair, returning the previous value for the key if there was one.
+    ///
+    /// Fails if the key is new and the StackMap is already full.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        match self.try_insert(key, value) {
+            Ok(old) => old,
+            Err(_) => fail!("StackMap::insert: capacity of {} exceeded", self.capacity()),
+        }
+    }
+
+    /// Insert a key-value pair, returning the previous value for the key if there was one.
+    ///
+    /// If the key is new and the StackMap is already full, the pair is handed back as `Err`.
+    pub fn try_insert(&mut self, key: K, value: V) -> Result<Option<V>, (K, V)> {
+        match self.search(&key) {
+            Ok(idx) => {
+                let (_, old) = mem::replace(self.vec.get_mut(idx), (key, value));
+                Ok(Some(old))
+            },
+            Err(_) if self.vec.is_full() => Err((key, value)),
+            Err(idx) => {
+                self.vec.insert(idx, (key, value));
+                Ok(None)
+            },
+        }
+    }
+
+    /// Remove the pair for the given key, returning its value if there was one.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        match self.search(key) {
+            Ok(idx) => {
+                let (_, v) = self.vec.remove(idx);
+                Some(v)
+            },
+            Err(_) => None,
+        }
+    }
+
+    /// Remove all pairs from the StackMap.
+    #[inline]
+    pub fn clear(&mut self) { self.vec.clear() }
+
+    /// Return the entry for the given key for in-place manipulation.
+    pub fn entry<'a>(&'a mut self, key: K) -> Entry<'a, K, V, A> {
+        match self.search(&key) {
+            Ok(idx) => Occupied(OccupiedEntry { map: self, idx: idx }),
+            Err(idx) => Vacant(VacantEntry { map: self, key: key, idx: idx }),
+        }
+    }
+
+    /// Return an iterator over the pairs in ascending key order.
+    #[inline]
+    pub fn iter<'a>(&'a self) -> Entries<'a, K, V, A> {
+        Entries { vec: &self.vec, idx: 0, end: self.vec.len() }
+    }
+
+    /// Return an iterator over the pairs whose keys lie within `[min, max)`, in ascending order.
+    pub fn range<'a>(&'a self, min: &K, max: &K) -> Entries<'a, K, V, A> {
+        let idx = self.lower_bound(min);
+        let end = self.lower_bound(max);
+        Entries { vec: &self.vec, idx: idx, end: if end < idx { idx } else { end } }
+    }
+
+    /// Return the pair with the smallest key.
+    pub fn first(&self) -> Option<(&K, &V)> {
+        if self.vec.is_empty() { return None }
+        let &(ref k, ref v) = self.vec.get(0);
+        Some((k, v))
+    }
+
+    /// Return the pair with the largest key.
+    pub fn last(&self) -> Option<(&K, &V)> {
+        if self.vec.is_empty() { return None }
+        let &(ref k, ref v) = self.vec.get(self.vec.len() - 1);
+        Some((k, v))
+    }
+
+}
+
+/// A struct for iterating over a StackMap's pairs in key order.
+pub struct Entries<'a, K, V, A: 'a> {
+    vec: &'a StackVec<A>,
+    idx: uint,
+    end: uint,
+}
+
+impl<'a, K, V, A: Storage<(K, V)> + 'a> Iterator<(&'a K, &'a V)> for Entries<'a, K, V, A> {
+    #[inline]
+    fn next(&mut self) -> Option<(&'a K, &'a V)> {
+        if self.idx < self.end {
+            let &(ref k, ref v) = self.vec.get(self.idx);
+            self.idx += 1;
+            Some((k, v))
+        } else { None }
+    }
+    #[inline]
+    fn size_hint(&self) -> (uint, Option<uint>) {
+        let remaining = self.end - self.idx;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<'a, K, V, A: Storage<(K, V)> + 'a> DoubleEndedIterator<(&'a K, &'a V)> for Entries<'a, K, V, A> {
+    #[inline]
+    fn next_back(&mut self) -> Option<(&'a K, &'a V)> {
+        if self.idx < self.end {
+            self.end -= 1;
+            let &(ref k, ref v) = self.vec.get(self.end);
+            Some((k, v))
+        } else { None }
+    }
+}
+
+/// A view into a single key's slot within a StackMap.
+pub enum Entry<'a, K: 'a, V: 'a, A: 'a> {
+    /// The key is present within the map.
+    Occupied(OccupiedEntry<'a, K, V, A>),
+    /// The key is absent from the map.
+    Vacant(VacantEntry<'a, K, V, A>),
+}
+
+/// A view into an occupied entry of a StackMap.
+pub struct OccupiedEntry<'a, K: 'a, V: 'a, A: 'a> {
+    map: &'a mut StackMap<A>,
+    idx: uint,
+}
+
+/// A view into a vacant entry of a StackMap.
+pub struct VacantEntry<'a, K: 'a, V: 'a, A: 'a> {
+    map: &'a mut StackMap<A>,
+    key: K,
+    idx: uint,
+}
+
+impl<'a, K: Ord, V, A: Storage<(K, V)>> OccupiedEntry<'a, K, V, A> {
+
+    /// Return an immutable reference to the entry's value.
+    pub fn get(&self) -> &V {
+        let &(_, ref v) = self.map.vec.get(self.idx);
+        v
+    }
+
+    /// Return a mutable reference to the entry's value.
+    pub fn get_mut(&mut self) -> &mut V {
+        match *self.map.vec.get_mut(self.idx) {
+            (_, ref mut v) => v,
+        }
+    }
+
+    /// Convert the entry into a mutable reference to its value.
+    pub fn into_mut(self) -> &'a mut V {
+        let OccupiedEntry { map, idx } = self;
+        match *map.vec.get_mut(idx) {
+            (_, ref mut v) => v,
+        }
+    }
+
+    /// Replace the entry's value, returning the old value.
+    pub fn insert(&mut self, value: V) -> V {
+        mem::replace(self.get_mut(), value)
+    }
+
+    /// Remove the entry from the map, returning its value.
+    pub fn take(self) -> V {
+        let (_, v) = self.map.vec.remove(self.idx);
+        v
+    }
+
+}
+
+impl<'a, K: Ord, V, A: Storage<(K, V)>> VacantEntry<'a, K, V, A> {
+
+    /// Insert the entry's key with the given value, returning a mutable reference to the value.
+    ///
+    /// Fails if the StackMap is already full.
+    pub fn insert(self, value: V) -> &'a mut V {
+        match self.try_insert(value) {
+            Ok(v) => v,
+            Err(_) => fail!("VacantEntry::insert: StackMap capacity exceeded"),
+        }
+    }
+
+    /// Insert the entry's key with the given value, handing the pair back if the StackMap is full.
+    pub fn try_insert(self, value: V) -> Result<&'a mut V, (K, V)> {
+        let VacantEntry { map, key, idx } = self;
+        if map.vec.is_full() {
+            return Err((key, value));
+        }
+        map.vec.insert(idx, (key, value));
+        match *map.vec.get_mut(idx) {
+            (_, ref mut v) => Ok(v),
+        }
+    }
+
+}
+
+
+#[test]
+fn insert_get_remove() {
+    use N4;
+
+    let mut map: StackMap<N4<(uint, &'static str)>> = StackMap::new();
+    assert_eq!(map.insert(3, "c"), None);
+    assert_eq!(map.insert(1, "a"), None);
+    assert_eq!(map.insert(2, "b"), None);
+    assert_eq!(map.insert(2, "B"), Some("b"));
+    assert_eq!(map.get(&2), Some(&"B"));
+    assert_eq!(map.try_insert(4, "d"), Ok(None));
+    assert_eq!(map.try_insert(5, "e"), Err((5, "e")));
+
+    let keys: Vec<uint> = map.iter().map(|(k, _)| *k).collect();
+    assert_eq!(keys, vec![1, 2, 3, 4]);
+    let keys: Vec<uint> = map.range(&2, &4).map(|(k, _)| *k).collect();
+    assert_eq!(keys, vec![2, 3]);
+
+    assert_eq!(map.remove(&1), Some("a"));
+    assert_eq!(map.remove(&1), None);
+    match map.entry(1) {
+        Vacant(entry) => { entry.insert("z"); },
+        Occupied(_) => fail!(),
+    }
+    assert_eq!(map.first(), Some((&1, &"z")));
+}
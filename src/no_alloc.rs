@@ -0,0 +1,224 @@
+//! Exercise every collection within `assert_no_alloc`, for every capacity
+//! from `N2` to `N1024`.
+
+use alloc_check::assert_no_alloc;
+
+macro_rules! no_alloc_test(
+    ($name:ident, $N:ident, $Slots:ident) => (
+        #[test]
+        fn $name() {
+            use bit_set::B1024;
+            use list::Node;
+            use {$N, $Slots, HybridVec, PoolSlot, SortedStackVec, SparseStackVec, StackArena,
+                 StackFrames, StackGrid, StackHashMap, StackHashSet, StackList, StackMap,
+                 StackPool, StackSet, StackVec};
+
+            fn zero() -> uint { 0 }
+
+            assert_no_alloc(|| {
+                let mut vec: StackVec<$N<uint>> = StackVec::new();
+                for i in range(0, vec.size() - 1) {
+                    vec.push(i);
+                }
+                vec.insert(0, 7);
+                assert_eq!(vec.try_push(0), Err(0));
+                vec.set(1, 3);
+                vec.remove(0);
+                vec.retain(|x| *x % 2 == 1);
+                let sum = vec.iter().fold(0, |acc, x| acc + *x);
+                vec.truncate(1);
+                vec.pop();
+                vec.clear();
+
+                let size = vec.size();
+                for i in range(0, size) {
+                    vec.push(i);
+                }
+                for x in vec.iter_mut() {
+                    *x += 1;
+                }
+                *vec.get_mut(0) = 0;
+                vec.as_mut_slice().reverse();
+                assert_eq!(vec.drain().next_back(), Some(0));
+                assert!(vec.is_empty());
+
+                let mut sparse: SparseStackVec<$Slots<uint>> = SparseStackVec::new();
+                while sparse.try_push(sum).is_ok() {}
+                sparse.remove(0);
+                sparse.compact();
+
+                let mut list: StackList<$Slots<Node<uint>>> = StackList::new();
+                let front = list.push_front(1);
+                list.push_back(2);
+                list.move_to_back(front);
+                assert_eq!(list.pop_front(), Some(2));
+
+                let mut map: StackMap<$N<(uint, uint)>> = StackMap::new();
+                let mut hash_map: StackHashMap<$Slots<(uint, uint)>> = StackHashMap::new();
+                let mut set: StackSet<$N<uint>> = StackSet::new();
+                let mut hash_set: StackHashSet<$Slots<(uint, ())>> = StackHashSet::new();
+                let mut sorted: SortedStackVec<$N<uint>> = SortedStackVec::new();
+                let mut hybrid: HybridVec<uint, $N<uint>> = HybridVec::new();
+                for i in range(0, size) {
+                    let key = size - 1 - i;
+                    map.insert(key, i);
+                    hash_map.insert(key, i);
+                    set.insert(key);
+                    hash_set.insert(key);
+                    sorted.insert(key);
+                    hybrid.push(key);
+                }
+                assert_eq!(map.try_insert(size, 0), Err((size, 0)));
+                assert_eq!(hash_map.try_insert(size, 0), Err((size, 0)));
+                assert_eq!(set.try_insert(size), Err(size));
+                assert_eq!(hash_set.try_insert(size), Err(size));
+                assert_eq!(sorted.try_insert(size), Err(size));
+                assert!(!hybrid.spilled());
+                *map.get_mut(&0).unwrap() += 1;
+                *hash_map.get_mut(&0).unwrap() += 1;
+                assert_eq!(map.remove(&0), hash_map.remove(&0));
+                assert!(set.remove(&0) && hash_set.remove(&0));
+                assert_eq!(map.iter().count(), hash_map.iter().count());
+                assert_eq!(set.iter().count(), hash_set.iter().count());
+                assert_eq!(sorted.pop_min(), Some(0));
+                assert_eq!(hybrid.pop(), 0);
+                assert_eq!(hybrid.iter().count(), sorted.len());
+
+                let mut grid: StackGrid<$N<uint>> = StackGrid::from_elem(2, size / 2, 0);
+                grid[(1, 0)] = 1;
+                *grid.get_mut(0, 0) = 2;
+                assert_eq!(grid.transpose()[(0, 1)], 1);
+                assert_eq!(grid.column(0).count(), 2);
+
+                let mut frames: StackFrames<$N<f32>> = StackFrames::new(2);
+                while frames.len() < frames.size() {
+                    frames.push_frame([0.5f32, -0.5].as_slice());
+                }
+                frames.scale_channel(0, 2.0);
+                frames.mix_channel(0, 1, 1.0);
+                assert_eq!(frames.channel(1).count(), size / 2);
+                frames.clear();
+
+                let arena: StackArena<$N<u8>> = StackArena::new();
+                assert_eq!(*arena.alloc(1u8), 1);
+                assert!(arena.try_alloc_slice([0u8, ..2048].as_slice()).is_err());
+
+                let pool: StackPool<uint, $Slots<PoolSlot<uint>>, B1024> = StackPool::new(zero);
+                {
+                    let mut voice = pool.acquire().unwrap();
+                    *voice += 1;
+                }
+                assert_eq!(pool.in_use(), 0);
+            });
+        }
+    )
+)
+
+no_alloc_test!(no_alloc_n2, N2, Slots2)
+no_alloc_test!(no_alloc_n4, N4, Slots4)
+no_alloc_test!(no_alloc_n8, N8, Slots8)
+no_alloc_test!(no_alloc_n16, N16, Slots16)
+no_alloc_test!(no_alloc_n32, N32, Slots32)
+no_alloc_test!(no_alloc_n64, N64, Slots64)
+no_alloc_test!(no_alloc_n128, N128, Slots128)
+no_alloc_test!(no_alloc_n256, N256, Slots256)
+no_alloc_test!(no_alloc_n512, N512, Slots512)
+no_alloc_test!(no_alloc_n1024, N1024, Slots1024)
+
+#[test]
+fn no_alloc_bit_sets() {
+    use bit_set::{B64, B128, B256, B512, B1024, BitSetWords, StackBitSet};
+    use {N64, StackVec};
+
+    fn exercise<W: BitSetWords>() {
+        let mut a: StackBitSet<W> = StackBitSet::new();
+        let mut b: StackBitSet<W> = StackBitSet::new();
+        let capacity = a.capacity();
+        for bit in range(0, capacity) {
+            if bit % 3 == 0 { a.insert(bit); }
+            if bit % 2 == 0 { b.insert(bit); }
+        }
+        assert!(a.remove(0) && !a.remove(0));
+        assert_eq!(a.first_set(), Some(3));
+        assert_eq!(b.first_clear(), Some(1));
+        assert_eq!(a.iter().count(), a.count_ones());
+        a.union_with(&b);
+        a.intersect_with(&b);
+        assert!(a.is_subset(&b));
+        a.symmetric_difference_with(&b);
+        a.difference_with(&b);
+        assert!(a.is_disjoint(&b));
+        b.clear();
+        b.insert(capacity - 1);
+        let indices: StackVec<N64<uint>> = b.to_stack_vec();
+        let restored: StackBitSet<W> = StackBitSet::from_stack_vec(&indices);
+        assert!(restored.contains(capacity - 1));
+    }
+
+    assert_no_alloc(|| {
+        exercise::<B64>();
+        exercise::<B128>();
+        exercise::<B256>();
+        exercise::<B512>();
+        exercise::<B1024>();
+    });
+}
+
+#[test]
+#[cfg(feature = "num")]
+fn no_alloc_arithmetic() {
+    use {N64, StackVec};
+
+    assert_no_alloc(|| {
+        let mut a: StackVec<N64<f32>> = StackVec::new();
+        let mut b: StackVec<N64<f32>> = StackVec::new();
+        for i in range(0u, 64) {
+            a.push(i as f32);
+            b.push(1.0);
+        }
+        let sum = (a + b) - b;
+        let product = (a * b) / b;
+        assert_eq!(sum.as_slice(), product.as_slice());
+        let scaled = (a * 2.0f32 + 1.0f32 - 1.0f32) / 2.0f32;
+        assert_eq!(scaled.as_slice(), a.as_slice());
+
+        a.add_assign(b.as_slice());
+        a.sub_assign(b.as_slice());
+        a.mul_assign(b.as_slice());
+        a.div_assign(b.as_slice());
+        a.add_scalar(1.0);
+        a.sub_scalar(1.0);
+        a.mul_scalar(2.0);
+        a.div_scalar(2.0);
+        assert_eq!(a.sum(), 2016.0);
+        assert_eq!(a.dot(b.as_slice()), 2016.0);
+        assert_eq!(a.peak(), 63.0);
+        assert!(b.rms() == 1.0);
+
+        let mut mix = [0.0f32, ..64];
+        b.mix_into(mix.as_mut_slice(), 0.5);
+        assert_eq!(mix[63], 0.5);
+    });
+}
+
+#[test]
+fn no_alloc_formatting_and_encoding() {
+    use string::{StackString, Truncate};
+    use {Compact64, N64, StackVec};
+
+    let mut buf = [0u8, ..64];
+    assert_no_alloc(|| {
+        let msg: StackString<N64<u8>> =
+            format_args!(|args| StackString::format(Truncate, args), "voice {} stolen", 3u).unwrap();
+        assert_eq!(msg.as_slice(), "voice 3 stolen");
+
+        let mut bytes: Compact64<u8> = StackVec::new();
+        bytes.write_bytes(msg.as_slice().as_bytes());
+        let mut read = [0u8, ..5];
+        assert_eq!(bytes.cursor().read_bytes(read.as_mut_slice()), 5);
+
+        let n = bytes.encode_into(buf.as_mut_slice()).unwrap();
+        let (decoded, _): (Compact64<u8>, uint) = StackVec::decode(buf.slice_to(n)).unwrap();
+        assert_eq!(decoded.as_slice(), bytes.as_slice());
+    });
+}
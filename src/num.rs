@@ -0,0 +1,216 @@
+//! Element-wise arithmetic and common DSP reductions for numeric StackVecs.
+//!
+//! Only available with the `num` feature enabled.
+
+use std::num;
+use std::num::{Float, Num, Zero};
+use {ArrayStorage, Length, StackVec, Storage};
+
+impl<T: Num + Copy, A: Storage<T>, L: Length> StackVec<A, L> {
+
+    /// Add each element of `other` to the corresponding element of `self`.
+    ///
+    /// Fails if the lengths differ.
+    pub fn add_assign(&mut self, other: &[T]) {
+        for (a, b) in self.zip_mut(other) { *a = *a + *b; }
+    }
+
+    /// Subtract each element of `other` from the corresponding element of `self`.
+    ///
+    /// Fails if the lengths differ.
+    pub fn sub_assign(&mut self, other: &[T]) {
+        for (a, b) in self.zip_mut(other) { *a = *a - *b; }
+    }
+
+    /// Multiply each element of `self` by the corresponding element of `other`.
+    ///
+    /// Fails if the lengths differ.
+    pub fn mul_assign(&mut self, other: &[T]) {
+        for (a, b) in self.zip_mut(other) { *a = *a * *b; }
+    }
+
+    /// Divide each element of `self` by the corresponding element of `other`.
+    ///
+    /// Fails if the lengths differ.
+    pub fn div_assign(&mut self, other: &[T]) {
+        for (a, b) in self.zip_mut(other) { *a = *a / *b; }
+    }
+
+    /// Pair each of our elements with the corresponding element of `other`.
+    #[inline]
+    fn zip_mut<'a>(&'a mut self, other: &'a [T])
+        -> ::std::iter::Zip<::std::slice::MutItems<'a, T>, ::std::slice::Items<'a, T>>
+    {
+        assert!(self.len() == other.len(), "the StackVecs must be of equal length");
+        self.as_mut_slice().iter_mut().zip(other.iter())
+    }
+
+    /// Add the given offset to every element.
+    pub fn add_scalar(&mut self, offset: T) {
+        for a in self.as_mut_slice().iter_mut() { *a = *a + offset; }
+    }
+
+    /// Subtract the given offset from every element.
+    pub fn sub_scalar(&mut self, offset: T) {
+        for a in self.as_mut_slice().iter_mut() { *a = *a - offset; }
+    }
+
+    /// Multiply every element by the given gain.
+    pub fn mul_scalar(&mut self, gain: T) {
+        for a in self.as_mut_slice().iter_mut() { *a = *a * gain; }
+    }
+
+    /// Divide every element by the given divisor.
+    pub fn div_scalar(&mut self, divisor: T) {
+        for a in self.as_mut_slice().iter_mut() { *a = *a / divisor; }
+    }
+
+    /// Return the sum of all elements.
+    pub fn sum(&self) -> T {
+        self.iter().fold(Zero::zero(), |acc, a| acc + *a)
+    }
+
+    /// Return the sum of the products of each element with the corresponding element of `other`.
+    ///
+    /// Fails if the lengths differ.
+    pub fn dot(&self, other: &[T]) -> T {
+        assert!(self.len() == other.len(), "the StackVecs must be of equal length");
+        self.iter().zip(other.iter()).fold(Zero::zero(), |acc, (a, b)| acc + *a * *b)
+    }
+
+    /// Add each of our elements, multiplied by the given gain, onto the
+    /// corresponding element of `dst`.
+    ///
+    /// Fails if the lengths differ.
+    pub fn mix_into(&self, dst: &mut [T], gain: T) {
+        assert!(self.len() == dst.len(), "the StackVecs must be of equal length");
+        for (d, a) in dst.iter_mut().zip(self.iter()) { *d = *d + *a * gain; }
+    }
+
+}
+
+impl<T: Float, A: Storage<T>, L: Length> StackVec<A, L> {
+
+    /// Return the greatest absolute value of all elements, or zero if empty.
+    pub fn peak(&self) -> T {
+        self.iter().fold(Zero::zero(), |peak: T, a| {
+            let abs = a.abs();
+            if abs > peak { abs } else { peak }
+        })
+    }
+
+    /// Return the root mean square of all elements, or zero if empty.
+    pub fn rms(&self) -> T {
+        if self.is_empty() { return Zero::zero() }
+        let sum_sq = self.iter().fold(Zero::zero(), |acc: T, a| acc + *a * *a);
+        let len: T = num::cast(self.len()).unwrap();
+        (sum_sq / len).sqrt()
+    }
+
+}
+
+/// Build a new StackVec by applying `f` to the corresponding elements of `a` and `b`.
+fn zip_with<T: Copy, A: ArrayStorage<T>, L: Length>(a: &StackVec<A, L>, b: &StackVec<A, L>,
+                                                     f: |T, T| -> T) -> StackVec<A, L>
+{
+    assert!(a.len() == b.len(), "the StackVecs must be of equal length");
+    let mut vec = StackVec::new();
+    for (x, y) in a.iter().zip(b.iter()) {
+        vec.push(f(*x, *y));
+    }
+    vec
+}
+
+/// Build a new StackVec by applying `f` to each element of `a`.
+fn map_each<T: Copy, A: ArrayStorage<T>, L: Length>(a: &StackVec<A, L>, f: |T| -> T)
+    -> StackVec<A, L>
+{
+    let mut vec = StackVec::new();
+    for x in a.iter() {
+        vec.push(f(*x));
+    }
+    vec
+}
+
+impl<T: Num + Copy, A: ArrayStorage<T>, L: Length> Add<StackVec<A, L>, StackVec<A, L>>
+    for StackVec<A, L>
+{
+    #[inline]
+    fn add(&self, rhs: &StackVec<A, L>) -> StackVec<A, L> { zip_with(self, rhs, |a, b| a + b) }
+}
+
+impl<T: Num + Copy, A: ArrayStorage<T>, L: Length> Sub<StackVec<A, L>, StackVec<A, L>>
+    for StackVec<A, L>
+{
+    #[inline]
+    fn sub(&self, rhs: &StackVec<A, L>) -> StackVec<A, L> { zip_with(self, rhs, |a, b| a - b) }
+}
+
+impl<T: Num + Copy, A: ArrayStorage<T>, L: Length> Mul<StackVec<A, L>, StackVec<A, L>>
+    for StackVec<A, L>
+{
+    #[inline]
+    fn mul(&self, rhs: &StackVec<A, L>) -> StackVec<A, L> { zip_with(self, rhs, |a, b| a * b) }
+}
+
+impl<T: Num + Copy, A: ArrayStorage<T>, L: Length> Div<StackVec<A, L>, StackVec<A, L>>
+    for StackVec<A, L>
+{
+    #[inline]
+    fn div(&self, rhs: &StackVec<A, L>) -> StackVec<A, L> { zip_with(self, rhs, |a, b| a / b) }
+}
+
+impl_scalar_ops!(f32)
+impl_scalar_ops!(f64)
+impl_scalar_ops!(i8)
+impl_scalar_ops!(i16)
+impl_scalar_ops!(i32)
+impl_scalar_ops!(i64)
+impl_scalar_ops!(int)
+impl_scalar_ops!(u8)
+impl_scalar_ops!(u16)
+impl_scalar_ops!(u32)
+impl_scalar_ops!(u64)
+impl_scalar_ops!(uint)
+
+
+#[test]
+fn arithmetic_and_reductions() {
+    use N4;
+
+    let mut a: StackVec<N4<f32>> = StackVec::new();
+    let mut b: StackVec<N4<f32>> = StackVec::new();
+    for &(x, y) in [(1.0f32, 0.5f32), (-3.0, 0.5), (2.0, 2.0)].iter() {
+        a.push(x);
+        b.push(y);
+    }
+
+    assert_eq!((a + b).as_slice(), [1.5f32, -2.5, 4.0].as_slice());
+    assert_eq!((a * b).as_slice(), [0.5f32, -1.5, 4.0].as_slice());
+    assert_eq!((a * 2.0f32).as_slice(), [2.0f32, -6.0, 4.0].as_slice());
+    assert_eq!(a.sum(), 0.0);
+    assert_eq!(a.dot(b.as_slice()), 3.0);
+    assert_eq!(a.peak(), 3.0);
+
+    let mut mix = [0.0f32, 0.0, 0.0];
+    b.mix_into(mix.as_mut_slice(), 2.0);
+    assert_eq!(mix.as_slice(), [1.0f32, 1.0, 4.0].as_slice());
+
+    b.add_scalar(1.0);
+    assert_eq!(b.as_slice(), [1.5f32, 1.5, 3.0].as_slice());
+    b.sub_scalar(0.5);
+    assert_eq!(b.as_slice(), [1.0f32, 1.0, 2.5].as_slice());
+    b.mul_scalar(4.0);
+    assert_eq!(b.as_slice(), [4.0f32, 4.0, 10.0].as_slice());
+    b.div_scalar(2.0);
+    assert_eq!(b.as_slice(), [2.0f32, 2.0, 5.0].as_slice());
+
+    let mut compact: StackVec<N4<f32>, u8> = StackVec::new();
+    compact.push(1.0);
+    compact.add_scalar(1.0);
+    assert_eq!(compact.sum(), 2.0);
+
+    let copy = a.as_slice().to_vec();
+    a.sub_assign(copy.as_slice());
+    assert_eq!(a.rms(), 0.0);
+}
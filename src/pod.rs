@@ -0,0 +1,283 @@
+//! Zero-copy byte views of StackVecs holding plain old data, for sending
+//! fixed buffers over shared memory or sockets.
+//!
+//! Only available with the `pod` feature enabled.
+//!
+//! Plain arrays of `Pod` types, e.g. `[f32, ..64]`, may also be used as a
+//! StackVec's storage. A StackVec's own layout includes its drop flag and
+//! padding, so only its elements are exposed as bytes, through `as_bytes`
+//! and `from_bytes`. To share the length along with the elements, use a
+//! `PodStackVec`, whose whole `#[repr(C)]` layout may be copied byte for byte
+//! and validated on the other side with `from_raw_bytes`.
+
+use std::mem;
+use std::ptr;
+use std::raw;
+use {ArrayStorage, Length, StackVec, Storage};
+
+/// Types that are "plain old data": they may be copied byte for byte, and any
+/// pattern of bytes of the right size is a valid value.
+///
+/// This must only be implemented for types without padding, pointers or
+/// invalid bit patterns, e.g. the primitive integer and float types and
+/// `#[repr(C)]` structs and arrays composed of them.
+pub trait Pod: Copy {}
+
+/// The ways in which bytes may fail to form valid values.
+#[deriving(Clone, PartialEq, Eq, Show)]
+pub enum PodError {
+    /// The number of bytes does not fit the expected type.
+    WrongLength,
+    /// The bytes are not sufficiently aligned for the expected type.
+    Misaligned,
+    /// The length of the encoded StackVec exceeds its capacity.
+    CapacityExceeded,
+}
+
+impl_pod!(u8)
+impl_pod!(u16)
+impl_pod!(u32)
+impl_pod!(u64)
+impl_pod!(uint)
+impl_pod!(i8)
+impl_pod!(i16)
+impl_pod!(i32)
+impl_pod!(i64)
+impl_pod!(int)
+impl_pod!(f32)
+impl_pod!(f64)
+
+impl_pod_array!(2)
+impl_pod_array!(4)
+impl_pod_array!(8)
+impl_pod_array!(16)
+impl_pod_array!(32)
+impl_pod_array!(64)
+impl_pod_array!(128)
+impl_pod_array!(256)
+impl_pod_array!(512)
+impl_pod_array!(1024)
+
+/// View the given value as a slice of bytes.
+#[inline]
+fn bytes_of<'a, T>(ptr: *const T, len: uint) -> &'a [u8] {
+    unsafe { mem::transmute(raw::Slice { data: ptr as *const u8, len: len * mem::size_of::<T>() }) }
+}
+
+/// Check that the given bytes may be viewed as `len` values of `T`.
+fn check_cast<T>(bytes: &[u8], len: uint) -> Result<(), PodError> {
+    if bytes.len() != len * mem::size_of::<T>() {
+        Err(WrongLength)
+    } else if bytes.as_ptr() as uint % mem::min_align_of::<T>() != 0 {
+        Err(Misaligned)
+    } else {
+        Ok(())
+    }
+}
+
+/// View the given bytes as a slice of `T` without copying.
+///
+/// Fails with `WrongLength` if the bytes are not a whole number of `T`, or with
+/// `Misaligned` if they are not aligned for `T`.
+pub fn cast_slice<'a, T: Pod>(bytes: &'a [u8]) -> Result<&'a [T], PodError> {
+    let size = mem::size_of::<T>();
+    if size == 0 || bytes.len() % size != 0 {
+        return Err(WrongLength);
+    }
+    let len = bytes.len() / size;
+    try!(check_cast::<T>(bytes, len));
+    Ok(unsafe { mem::transmute(raw::Slice { data: bytes.as_ptr() as *const T, len: len }) })
+}
+
+impl<T: Pod, A: Storage<T>, L: Length> StackVec<A, L> {
+
+    /// View the elements as a slice of bytes.
+    #[inline]
+    pub fn as_bytes<'a>(&'a self) -> &'a [u8] {
+        bytes_of(self.as_slice().as_ptr(), self.len())
+    }
+
+    /// View the elements as a mutable slice of bytes.
+    #[inline]
+    pub fn as_bytes_mut<'a>(&'a mut self) -> &'a mut [u8] {
+        let len = self.len();
+        unsafe { mem::transmute(bytes_of(self.as_mut_slice().as_ptr(), len)) }
+    }
+
+}
+
+impl<T: Pod, A: ArrayStorage<T>, L: Length> StackVec<A, L> {
+
+    /// Create a StackVec holding a copy of the elements within the given bytes,
+    /// which need not be aligned.
+    pub fn from_bytes(bytes: &[u8]) -> Result<StackVec<A, L>, PodError> {
+        let size = mem::size_of::<T>();
+        if size == 0 || bytes.len() % size != 0 {
+            return Err(WrongLength);
+        }
+        let mut vec: StackVec<A, L> = StackVec::new();
+        if bytes.len() / size > vec.size() {
+            return Err(CapacityExceeded);
+        }
+        for chunk in bytes.chunks(size) {
+            let elem: T = unsafe { ptr::read(chunk.as_ptr() as *const T) };
+            vec.push(elem);
+        }
+        Ok(vec)
+    }
+
+}
+
+
+/// A vector of plain old data with a stable `#[repr(C)]` layout: a `u64`
+/// length followed by the array `A`, e.g. `PodStackVec<[f32, ..64]>`.
+///
+/// Unlike a StackVec it has no destructor and so no drop flag, and as the
+/// length is as wide as the alignment of any primitive element, there is no
+/// padding either side of it. The whole vector may therefore be copied into
+/// shared memory or onto a socket with `as_raw_bytes` and viewed again with
+/// `from_raw_bytes`, which checks that the length is within the capacity.
+#[repr(C)]
+pub struct PodStackVec<A> {
+    len: u64,
+    data: A,
+}
+
+impl<T: Pod, A: Pod + ArrayStorage<T>> PodStackVec<A> {
+
+    /// Create an empty PodStackVec, with every element zeroed.
+    #[inline]
+    pub fn new() -> PodStackVec<A> {
+        PodStackVec { len: 0, data: ArrayStorage::new() }
+    }
+
+}
+
+impl<T: Pod, A: Pod + Storage<T>> PodStackVec<A> {
+
+    /// View the given bytes, e.g. those of a PodStackVec received from another
+    /// process, as a PodStackVec without copying.
+    ///
+    /// Fails with `WrongLength` or `Misaligned` if the bytes cannot hold a
+    /// PodStackVec, or with `CapacityExceeded` if its length exceeds its capacity.
+    pub fn from_raw_bytes<'a>(bytes: &'a [u8]) -> Result<&'a PodStackVec<A>, PodError> {
+        try!(check_cast::<PodStackVec<A>>(bytes, 1));
+        let vec: &'a PodStackVec<A> = unsafe { &*(bytes.as_ptr() as *const PodStackVec<A>) };
+        if vec.len > vec.data.capacity() as u64 {
+            return Err(CapacityExceeded);
+        }
+        Ok(vec)
+    }
+
+    /// View the whole PodStackVec, including its length and unoccupied
+    /// elements, as a slice of bytes.
+    #[inline]
+    pub fn as_raw_bytes<'a>(&'a self) -> &'a [u8] {
+        bytes_of(self as *const PodStackVec<A>, 1)
+    }
+
+    /// Return the number of elements.
+    #[inline]
+    pub fn len(&self) -> uint { self.len as uint }
+
+    /// Return the maximum number of elements the PodStackVec can hold.
+    #[inline]
+    pub fn size(&self) -> uint { self.data.capacity() }
+
+    /// Return true if the PodStackVec contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.len == 0 }
+
+    /// Return true if no more elements can be pushed.
+    #[inline]
+    pub fn is_full(&self) -> bool { self.len() == self.size() }
+
+    /// Push an element onto the end of the PodStackVec.
+    #[inline]
+    pub fn push(&mut self, elem: T) {
+        assert!(!self.is_full(), "PodStackVec::push: capacity exceeded");
+        let len = self.len();
+        unsafe { ptr::write(self.data.as_mut_ptr().offset(len as int), elem) }
+        self.len += 1;
+    }
+
+    /// Push an element onto the end of the PodStackVec, or hand it back as `Err` if it is full.
+    #[inline]
+    pub fn try_push(&mut self, elem: T) -> Result<(), T> {
+        if self.is_full() {
+            return Err(elem);
+        }
+        self.push(elem);
+        Ok(())
+    }
+
+    /// Remove and return the final element, if any.
+    #[inline]
+    pub fn pop(&mut self) -> Option<T> {
+        if self.is_empty() {
+            return None;
+        }
+        self.len -= 1;
+        Some(unsafe { *self.data.as_ptr().offset(self.len as int) })
+    }
+
+    /// Remove all elements.
+    #[inline]
+    pub fn clear(&mut self) { self.len = 0; }
+
+    /// Return the elements as a slice.
+    #[inline]
+    pub fn as_slice<'a>(&'a self) -> &'a [T] {
+        unsafe { mem::transmute(raw::Slice { data: self.data.as_ptr(), len: self.len() }) }
+    }
+
+    /// Return the elements as a mutable slice.
+    #[inline]
+    pub fn as_mut_slice<'a>(&'a mut self) -> &'a mut [T] {
+        let len = self.len();
+        unsafe {
+            mem::transmute(raw::Slice { data: self.data.as_mut_ptr() as *const T, len: len })
+        }
+    }
+
+}
+
+#[test]
+fn byte_views_round_trip() {
+    let mut vec: StackVec<[f32, ..8]> = StackVec::new();
+    vec.push(1.0);
+    vec.push(-0.5);
+    assert_eq!(vec.as_bytes().len(), 8);
+    assert_eq!(cast_slice::<f32>(vec.as_bytes()), Ok([1.0f32, -0.5].as_slice()));
+
+    let copy: StackVec<[f32, ..8]> = StackVec::from_bytes(vec.as_bytes()).unwrap();
+    assert_eq!(copy.as_slice(), vec.as_slice());
+    let too_long: Result<StackVec<[f32, ..2]>, PodError> = StackVec::from_bytes([0u8, ..12].as_slice());
+    assert_eq!(too_long.err(), Some(CapacityExceeded));
+}
+
+#[test]
+fn pod_stack_vec_raw_round_trip() {
+    let mut vec: PodStackVec<[f32, ..4]> = PodStackVec::new();
+    vec.push(0.5);
+    vec.push(0.25);
+    {
+        let bytes = vec.as_raw_bytes();
+        assert_eq!(bytes.len(), 8 + 4 * 4);
+        let view: &PodStackVec<[f32, ..4]> = PodStackVec::from_raw_bytes(bytes).unwrap();
+        assert_eq!(view.as_slice(), [0.5f32, 0.25].as_slice());
+        let short: Result<&PodStackVec<[f32, ..4]>, PodError> =
+            PodStackVec::from_raw_bytes(bytes.slice_to(8));
+        assert_eq!(short.err(), Some(WrongLength));
+    }
+
+    let mut corrupt = vec;
+    corrupt.len = 5;
+    let corrupt: Result<&PodStackVec<[f32, ..4]>, PodError> =
+        PodStackVec::from_raw_bytes(corrupt.as_raw_bytes());
+    assert_eq!(corrupt.err(), Some(CapacityExceeded));
+
+    assert_eq!(vec.pop(), Some(0.25));
+    assert_eq!(vec.try_push(1.0), Ok(()));
+    assert_eq!(vec.as_slice(), [0.5f32, 1.0].as_slice());
+}
@@ -0,0 +1,178 @@
+//! A pool of reusable objects, e.g. synth voices, living within fixed-size stack storage.
+
+use bit_set::{BitSetWords, StackBitSet};
+use std::cell::UnsafeCell;
+use std::kinds::marker;
+use StackVecArray;
+
+/// A fixed-size pool of objects that are handed out and returned rather than
+/// constructed and dropped.
+///
+/// Objects are created by the pool's `create` function, either all at once
+/// upon creation of the pool or lazily the first time each slot is acquired.
+/// Which slots are currently handed out is tracked by a StackBitSet, whose
+/// capacity must be at least that of the slot array.
+///
+/// Each slot is a `PoolSlot`, e.g. `StackPool<Voice, Slots16<PoolSlot<Voice>>, B64>`,
+/// so that a guard may access its own slot without borrowing the whole array.
+pub struct StackPool<T, A, W> {
+    slots: A,
+    in_use: UnsafeCell<StackBitSet<W>>,
+    create: fn() -> T,
+    reset: Option<fn(&mut T)>,
+    no_sync: marker::NoSync,
+}
+
+/// A single slot of a StackPool, holding its object once it has been created.
+pub type PoolSlot<T> = UnsafeCell<Option<T>>;
+
+impl<T, A: StackVecArray<PoolSlot<T>>, W: BitSetWords> StackPool<T, A, W> {
+
+    /// Create a pool that creates each object the first time its slot is acquired.
+    pub fn new(create: fn() -> T) -> StackPool<T, A, W> {
+        let mut slots: A = StackVecArray::new();
+        for idx in range(0, slots.size()) {
+            slots.set(idx, UnsafeCell::new(None));
+        }
+        let pool = StackPool {
+            slots: slots,
+            in_use: UnsafeCell::new(StackBitSet::new()),
+            create: create,
+            reset: None,
+            no_sync: marker::NoSync,
+        };
+        assert!(pool.bits().capacity() >= pool.capacity(),
+                "StackPool::new: the bit set is smaller than the slot array");
+        pool
+    }
+
+    /// Create a pool with every object created up front.
+    pub fn prefilled(create: fn() -> T) -> StackPool<T, A, W> {
+        let pool = StackPool::new(create);
+        for idx in range(0, pool.capacity()) {
+            unsafe { *pool.slot(idx) = Some(create()) }
+        }
+        pool
+    }
+
+    /// Set a hook that is called upon each object as it is returned to the pool.
+    #[inline]
+    pub fn set_reset_hook(&mut self, reset: fn(&mut T)) {
+        self.reset = Some(reset);
+    }
+
+    /// Return the number of objects the pool can hand out at once.
+    #[inline]
+    pub fn capacity(&self) -> uint { self.slots.size() }
+
+    /// Return the number of objects currently handed out.
+    #[inline]
+    pub fn in_use(&self) -> uint { self.bits().count_ones() }
+
+    /// Return the number of objects that may still be acquired.
+    #[inline]
+    pub fn available(&self) -> uint { self.capacity() - self.in_use() }
+
+    /// Return the set of slots that are currently handed out.
+    #[inline]
+    fn bits(&self) -> &StackBitSet<W> { unsafe { &*self.in_use.get() } }
+
+    /// Return a pointer to the contents of the slot at the given index.
+    #[inline]
+    fn slot(&self, idx: uint) -> *mut Option<T> { self.slots.get(idx).get() }
+
+    /// Acquire an object from the pool, or `None` if every object is in use.
+    ///
+    /// The object returns to the pool when the PoolGuard is dropped.
+    pub fn acquire<'a>(&'a self) -> Option<PoolGuard<'a, T, A, W>> {
+        let idx = match self.bits().first_clear() {
+            Some(idx) if idx < self.capacity() => idx,
+            _ => return None,
+        };
+        unsafe {
+            (*self.in_use.get()).insert(idx);
+            let slot = &mut *self.slot(idx);
+            if slot.is_none() {
+                *slot = Some((self.create)());
+            }
+        }
+        Some(PoolGuard { pool: self, idx: idx })
+    }
+
+}
+
+/// An object acquired from a StackPool, which returns it to the pool when dropped.
+pub struct PoolGuard<'a, T: 'a, A: 'a, W: 'a> {
+    pool: &'a StackPool<T, A, W>,
+    idx: uint,
+}
+
+impl<'a, T, A: StackVecArray<PoolSlot<T>>, W: BitSetWords> PoolGuard<'a, T, A, W> {
+
+    /// Return the index of the pool slot that holds the object.
+    #[inline]
+    pub fn slot(&self) -> uint { self.idx }
+
+}
+
+// Each slot is marked as in use for as long as its guard lives, so the guard
+// has exclusive access to the object within it. Only that slot's UnsafeCell is
+// ever borrowed mutably, never the array holding the other guards' slots.
+
+impl<'a, T, A: StackVecArray<PoolSlot<T>>, W: BitSetWords> Deref<T>
+    for PoolGuard<'a, T, A, W>
+{
+    #[inline]
+    fn deref<'b>(&'b self) -> &'b T {
+        unsafe { (*self.pool.slot(self.idx)).as_ref().unwrap() }
+    }
+}
+
+impl<'a, T, A: StackVecArray<PoolSlot<T>>, W: BitSetWords> DerefMut<T>
+    for PoolGuard<'a, T, A, W>
+{
+    #[inline]
+    fn deref_mut<'b>(&'b mut self) -> &'b mut T {
+        unsafe { (*self.pool.slot(self.idx)).as_mut().unwrap() }
+    }
+}
+
+#[unsafe_destructor]
+impl<'a, T, A: StackVecArray<PoolSlot<T>>, W: BitSetWords> Drop
+    for PoolGuard<'a, T, A, W>
+{
+    fn drop(&mut self) {
+        match self.pool.reset {
+            Some(reset) => reset(&mut **self),
+            None => (),
+        }
+        unsafe { (*self.pool.in_use.get()).remove(self.idx); }
+    }
+}
+
+
+#[test]
+fn acquire_and_release() {
+    use bit_set::B64;
+    use Slots2;
+
+    struct Voice { note: uint }
+    fn voice() -> Voice { Voice { note: 0 } }
+    fn silence(voice: &mut Voice) { voice.note = 0; }
+
+    let mut pool: StackPool<Voice, Slots2<PoolSlot<Voice>>, B64> = StackPool::new(voice);
+    pool.set_reset_hook(silence);
+    {
+        let mut a = pool.acquire().unwrap();
+        let mut b = pool.acquire().unwrap();
+        a.note = 60;
+        b.note = 64;
+        assert!(pool.acquire().is_none());
+        assert_eq!(pool.available(), 0);
+        assert_eq!(b.slot(), 1);
+    }
+    assert_eq!(pool.in_use(), 0);
+    let a = pool.acquire().unwrap();
+    assert_eq!(a.slot(), 0);
+    assert_eq!(a.note, 0);
+}
@@ -0,0 +1,284 @@
+//! A sorted, fixed-capacity set built on top of a StackVec.
+
+use std::iter::FromIterator;
+use {ArrayStorage, Items, StackVec, Storage};
+
+/// A set that stores its elements in ascending order within a StackVec.
+///
+/// Membership tests use a binary search and set operations walk both sets in
+/// order, so none of them allocate.
+pub struct StackSet<A> {
+    vec: StackVec<A>,
+}
+
+impl<T: Ord, A: ArrayStorage<T>> StackSet<A> {
+
+    /// Create an empty StackSet.
+    #[inline]
+    pub fn new() -> StackSet<A> {
+        StackSet::from_storage(ArrayStorage::new())
+    }
+
+}
+
+impl<T: Ord, A: Storage<T>> StackSet<A> {
+
+    /// Create an empty StackSet over the given storage.
+    #[inline]
+    pub fn from_storage(storage: A) -> StackSet<A> {
+        StackSet { vec: StackVec::from_storage(storage) }
+    }
+
+    /// Return the number of elements within the StackSet.
+    #[inline]
+    pub fn len(&self) -> uint { self.vec.len() }
+
+    /// Return true if the StackSet contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.vec.is_empty() }
+
+    /// Return true if no more elements can be inserted.
+    #[inline]
+    pub fn is_full(&self) -> bool { self.vec.is_full() }
+
+    /// Return the maximum number of elements the StackSet can hold.
+    #[inline]
+    pub fn capacity(&self) -> uint { self.vec.size() }
+
+    /// Binary search for the given value.
+    ///
+    /// Returns `Ok` with the index of the value if found, otherwise `Err` with
+    /// the index at which the value would be inserted.
+    fn search(&self, value: &T) -> Result<uint, uint> {
+        let (mut lo, mut hi) = (0u, self.vec.len());
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            match self.vec.get(mid).cmp(value) {
+                Less => lo = mid + 1,
+                Greater => hi = mid,
+                Equal => return Ok(mid),
+            }
+        }
+        Err(lo)
+    }
+
+    /// Return true if the StackSet contains the given value.
+    #[inline]
+    pub fn contains(&self, value: &T) -> bool { self.search(value).is_ok() }
+
+    /// Insert the given value, returning true if it was not already present.
+    ///
+    /// Fails if the value is new and the StackSet is already full.
+    pub fn insert(&mut self, value: T) -> bool {
+        match self.try_insert(value) {
+            Ok(inserted) => inserted,
+            Err(_) => fail!("StackSet::insert: capacity of {} exceeded", self.capacity()),
+        }
+    }
+
+    /// Insert the given value, returning true if it was not already present.
+    ///
+    /// If the value is new and the StackSet is already full, it is handed back as `Err`.
+    pub fn try_insert(&mut self, value: T) -> Result<bool, T> {
+        match self.search(&value) {
+            Ok(_) => Ok(false),
+            Err(_) if self.vec.is_full() => Err(value),
+            Err(idx) => {
+                self.vec.insert(idx, value);
+                Ok(true)
+            },
+        }
+    }
+
+    /// Remove the given value, returning true if it was present.
+    pub fn remove(&mut self, value: &T) -> bool {
+        match self.search(value) {
+            Ok(idx) => {
+                self.vec.remove(idx);
+                true
+            },
+            Err(_) => false,
+        }
+    }
+
+    /// Remove all elements from the StackSet.
+    #[inline]
+    pub fn clear(&mut self) { self.vec.clear() }
+
+    /// Return an iterator over the elements in ascending order.
+    #[inline]
+    pub fn iter<'a>(&'a self) -> Items<'a, T> { self.vec.iter() }
+
+    /// Return the smallest element.
+    pub fn first(&self) -> Option<&T> {
+        if self.vec.is_empty() { None } else { Some(self.vec.get(0)) }
+    }
+
+    /// Return the largest element.
+    pub fn last(&self) -> Option<&T> {
+        if self.vec.is_empty() { None } else { Some(self.vec.get(self.vec.len() - 1)) }
+    }
+
+    /// Return an iterator over the elements in either set, in ascending order.
+    #[inline]
+    pub fn union<'a>(&'a self, other: &'a StackSet<A>) -> Union<'a, T, A> {
+        Union { a: &self.vec, b: &other.vec, i: 0, j: 0 }
+    }
+
+    /// Return an iterator over the elements in both sets, in ascending order.
+    #[inline]
+    pub fn intersection<'a>(&'a self, other: &'a StackSet<A>) -> Intersection<'a, T, A> {
+        Intersection { a: &self.vec, b: &other.vec, i: 0, j: 0 }
+    }
+
+    /// Return an iterator over the elements in `self` but not in `other`, in ascending order.
+    #[inline]
+    pub fn difference<'a>(&'a self, other: &'a StackSet<A>) -> Difference<'a, T, A> {
+        Difference { a: &self.vec, b: &other.vec, i: 0, j: 0 }
+    }
+
+    /// Return an iterator over the elements in exactly one of the sets, in ascending order.
+    #[inline]
+    pub fn symmetric_difference<'a>(&'a self, other: &'a StackSet<A>)
+        -> SymmetricDifference<'a, T, A>
+    {
+        SymmetricDifference { a: &self.vec, b: &other.vec, i: 0, j: 0 }
+    }
+
+    /// Return true if every element of `self` is also in `other`.
+    pub fn is_subset(&self, other: &StackSet<A>) -> bool {
+        self.difference(other).next().is_none()
+    }
+
+    /// Return true if the sets have no elements in common.
+    pub fn is_disjoint(&self, other: &StackSet<A>) -> bool {
+        self.intersection(other).next().is_none()
+    }
+
+}
+
+impl<T: Ord, A: ArrayStorage<T>> FromIterator<T> for StackSet<A> {
+    fn from_iter<I: Iterator<T>>(iterator: I) -> StackSet<A> {
+        let mut set = StackSet::new();
+        for value in iterator {
+            set.insert(value);
+        }
+        set
+    }
+}
+
+/// Compare the next elements of two sorted StackVecs, if both have one left.
+#[inline]
+fn cmp_next<T: Ord, A: Storage<T>>(a: &StackVec<A>, i: uint, b: &StackVec<A>, j: uint)
+    -> Option<Ordering>
+{
+    if i < a.len() && j < b.len() { Some(a.get(i).cmp(b.get(j))) } else { None }
+}
+
+/// An iterator over the union of two StackSets.
+pub struct Union<'a, T, A: 'a> {
+    a: &'a StackVec<A>,
+    b: &'a StackVec<A>,
+    i: uint,
+    j: uint,
+}
+
+/// An iterator over the intersection of two StackSets.
+pub struct Intersection<'a, T, A: 'a> {
+    a: &'a StackVec<A>,
+    b: &'a StackVec<A>,
+    i: uint,
+    j: uint,
+}
+
+/// An iterator over the difference of two StackSets.
+pub struct Difference<'a, T, A: 'a> {
+    a: &'a StackVec<A>,
+    b: &'a StackVec<A>,
+    i: uint,
+    j: uint,
+}
+
+/// An iterator over the symmetric difference of two StackSets.
+pub struct SymmetricDifference<'a, T, A: 'a> {
+    a: &'a StackVec<A>,
+    b: &'a StackVec<A>,
+    i: uint,
+    j: uint,
+}
+
+impl<'a, T: Ord, A: Storage<T> + 'a> Iterator<&'a T> for Union<'a, T, A> {
+    fn next(&mut self) -> Option<&'a T> {
+        match cmp_next(self.a, self.i, self.b, self.j) {
+            Some(Less) => { self.i += 1; Some(self.a.get(self.i - 1)) },
+            Some(Greater) => { self.j += 1; Some(self.b.get(self.j - 1)) },
+            Some(Equal) => { self.i += 1; self.j += 1; Some(self.a.get(self.i - 1)) },
+            None if self.i < self.a.len() => { self.i += 1; Some(self.a.get(self.i - 1)) },
+            None if self.j < self.b.len() => { self.j += 1; Some(self.b.get(self.j - 1)) },
+            None => None,
+        }
+    }
+}
+
+impl<'a, T: Ord, A: Storage<T> + 'a> Iterator<&'a T> for Intersection<'a, T, A> {
+    fn next(&mut self) -> Option<&'a T> {
+        loop {
+            match cmp_next(self.a, self.i, self.b, self.j) {
+                Some(Less) => self.i += 1,
+                Some(Greater) => self.j += 1,
+                Some(Equal) => { self.i += 1; self.j += 1; return Some(self.a.get(self.i - 1)) },
+                None => return None,
+            }
+        }
+    }
+}
+
+impl<'a, T: Ord, A: Storage<T> + 'a> Iterator<&'a T> for Difference<'a, T, A> {
+    fn next(&mut self) -> Option<&'a T> {
+        loop {
+            match cmp_next(self.a, self.i, self.b, self.j) {
+                Some(Less) => { self.i += 1; return Some(self.a.get(self.i - 1)) },
+                Some(Greater) => self.j += 1,
+                Some(Equal) => { self.i += 1; self.j += 1; },
+                None if self.i < self.a.len() => { self.i += 1; return Some(self.a.get(self.i - 1)) },
+                None => return None,
+            }
+        }
+    }
+}
+
+impl<'a, T: Ord, A: Storage<T> + 'a> Iterator<&'a T> for SymmetricDifference<'a, T, A> {
+    fn next(&mut self) -> Option<&'a T> {
+        loop {
+            match cmp_next(self.a, self.i, self.b, self.j) {
+                Some(Less) => { self.i += 1; return Some(self.a.get(self.i - 1)) },
+                Some(Greater) => { self.j += 1; return Some(self.b.get(self.j - 1)) },
+                Some(Equal) => { self.i += 1; self.j += 1; },
+                None if self.i < self.a.len() => { self.i += 1; return Some(self.a.get(self.i - 1)) },
+                None if self.j < self.b.len() => { self.j += 1; return Some(self.b.get(self.j - 1)) },
+                None => return None,
+            }
+        }
+    }
+}
+
+
+#[test]
+fn set_algebra() {
+    use N8;
+
+    let a: StackSet<N8<uint>> = vec![5u, 1, 3, 7, 3].into_iter().collect();
+    let b: StackSet<N8<uint>> = vec![3u, 4, 5, 6].into_iter().collect();
+    assert_eq!(a.len(), 4);
+    assert!(a.contains(&7) && !a.contains(&4));
+
+    let union: Vec<uint> = a.union(&b).map(|x| *x).collect();
+    assert_eq!(union, vec![1, 3, 4, 5, 6, 7]);
+    let intersection: Vec<uint> = a.intersection(&b).map(|x| *x).collect();
+    assert_eq!(intersection, vec![3, 5]);
+    let difference: Vec<uint> = a.difference(&b).map(|x| *x).collect();
+    assert_eq!(difference, vec![1, 7]);
+    let symmetric: StackSet<N8<uint>> = a.symmetric_difference(&b).map(|x| *x).collect();
+    assert_eq!(symmetric.iter().map(|x| *x).collect::<Vec<uint>>(), vec![1, 4, 6, 7]);
+    assert!(!a.is_subset(&b) && !a.is_disjoint(&b));
+}
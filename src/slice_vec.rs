@@ -0,0 +1,95 @@
+//! A vector whose storage is a caller-provided buffer rather than an owned array.
+
+use std::kinds::marker;
+use {StackVec, Storage};
+
+/// Storage whose slots are borrowed from a caller-provided buffer.
+///
+/// Where a StackVec usually owns its fixed-size array, a StackVec over a
+/// SliceStorage borrows its slots, allowing vectors to be carved out of a
+/// static pool or a larger scratch buffer with a capacity that is only known
+/// at runtime.
+pub struct SliceStorage<'a, T: 'a> {
+    ptr: *mut T,
+    len: uint,
+    marker: marker::ContravariantLifetime<'a>,
+}
+
+/// A StackVec built over a borrowed buffer.
+pub type SliceVec<'a, T> = StackVec<SliceStorage<'a, T>>;
+
+impl<'a, T: Copy> SliceStorage<'a, T> {
+
+    /// Borrow the given buffer as storage.
+    ///
+    /// Its current values are ignored and will be overwritten. Only `Copy`
+    /// types are accepted, as the buffer's owner would otherwise drop values
+    /// that the StackVec has already moved out or dropped.
+    #[inline]
+    pub fn new(buf: &'a mut [T]) -> SliceStorage<'a, T> {
+        SliceStorage { ptr: buf.as_mut_ptr(), len: buf.len(), marker: marker::ContravariantLifetime }
+    }
+
+}
+
+impl<'a, T> SliceStorage<'a, T> {
+
+    /// Use the `len` possibly uninitialised slots starting at `ptr` as storage.
+    ///
+    /// The slots must remain valid and otherwise untouched for the lifetime
+    /// `'a`. Their current values are never read or dropped, and whatever the
+    /// StackVec leaves in them afterwards must not be dropped by their owner.
+    #[inline]
+    pub unsafe fn from_raw_parts(ptr: *mut T, len: uint) -> SliceStorage<'a, T> {
+        SliceStorage { ptr: ptr, len: len, marker: marker::ContravariantLifetime }
+    }
+
+}
+
+impl<'a, T> Storage<T> for SliceStorage<'a, T> {
+    #[inline]
+    fn as_ptr(&self) -> *const T { self.ptr as *const T }
+    #[inline]
+    fn as_mut_ptr(&mut self) -> *mut T { self.ptr }
+    #[inline]
+    fn capacity(&self) -> uint { self.len }
+}
+
+
+#[test]
+fn carve_from_pool() {
+    let mut pool = [0u, ..8];
+    let (a, b) = pool.as_mut_slice().split_at_mut(3);
+    let mut a: SliceVec<uint> = StackVec::from_storage(SliceStorage::new(a));
+    let mut b: SliceVec<uint> = StackVec::from_storage(SliceStorage::new(b));
+    for i in range(0u, 3) {
+        a.push(i);
+    }
+    assert!(a.is_full());
+    assert_eq!(a.try_push(3), Err(3));
+    b.push(10);
+    b.push(30);
+    b.insert(1, 20);
+    assert_eq!(b.as_slice(), [10u, 20, 30].as_slice());
+    assert_eq!(b.remove(0), 10);
+    assert_eq!(b.size(), 5);
+    assert_eq!(a.pop(), 2);
+    assert_eq!(b.pop(), 30);
+}
+
+#[test]
+fn over_uninitialised_buffer() {
+    use std::mem;
+
+    let mut buf: [String, ..4] = unsafe { mem::uninitialized() };
+    {
+        let storage = unsafe { SliceStorage::from_raw_parts(buf.as_mut_slice().as_mut_ptr(), 4) };
+        let mut names: SliceVec<String> = StackVec::from_storage(storage);
+        names.push("kick".to_string());
+        names.push("snare".to_string());
+        assert_eq!(names.remove(0).as_slice(), "kick");
+        assert_eq!(names.get(0).as_slice(), "snare");
+    }
+    // The StackVec has dropped its elements, so the buffer must not be dropped again.
+    unsafe { mem::forget(buf) }
+}
@@ -0,0 +1,355 @@
+//! Vectors that keep their elements in ascending order as they are inserted.
+//!
+//! SortedStackVec and SortedStackVecByKey differ only in how two elements are
+//! compared, so both are thin wrappers over the functions below, which take
+//! the comparison as a closure.
+
+use algo::partition_point;
+use {ArrayStorage, Items, StackVec, Storage};
+
+/// Insert `elem` after every element `e` for which `le(e, &elem)` holds,
+/// handing it back as `Err` if the vector is already full.
+fn try_insert_by<T, A: Storage<T>>(vec: &mut StackVec<A>, elem: T, le: |&T, &T| -> bool)
+    -> Result<uint, T>
+{
+    if vec.is_full() {
+        return Err(elem);
+    }
+    let idx = partition_point(vec.as_slice(), |e| le(e, &elem));
+    vec.insert(idx, elem);
+    Ok(idx)
+}
+
+/// Merge the two sorted slices into a new vector, taking from `a` first on ties.
+fn merge_by<T: Clone, A: ArrayStorage<T>>(a: &[T], b: &[T], le: |&T, &T| -> bool)
+    -> StackVec<A>
+{
+    let mut merged = StackVec::new();
+    let (mut a, mut b) = (a.iter().peekable(), b.iter().peekable());
+    loop {
+        let take_a = match (a.peek(), b.peek()) {
+            (None, None) => break,
+            (Some(_), None) => true,
+            (None, Some(_)) => false,
+            (Some(x), Some(y)) => le(*x, *y),
+        };
+        let elem = if take_a { a.next() } else { b.next() };
+        merged.push(elem.unwrap().clone());
+    }
+    merged
+}
+
+/// Return the elements that are not `below_min` but are `below_max`.
+fn range_by<'a, T>(slice: &'a [T], below_min: |&T| -> bool, below_max: |&T| -> bool) -> &'a [T] {
+    let start = partition_point(slice, below_min);
+    let end = partition_point(slice, below_max);
+    if start < end { slice.slice(start, end) } else { slice.slice(start, start) }
+}
+
+/// Return the first element, if any.
+#[inline]
+fn first<T, A: Storage<T>>(vec: &StackVec<A>) -> Option<&T> {
+    if vec.is_empty() { None } else { Some(vec.get(0)) }
+}
+
+/// Return the last element, if any.
+#[inline]
+fn last<T, A: Storage<T>>(vec: &StackVec<A>) -> Option<&T> {
+    if vec.is_empty() { None } else { Some(vec.get(vec.len() - 1)) }
+}
+
+/// Remove and return the first element, if any.
+#[inline]
+fn pop_first<T, A: Storage<T>>(vec: &mut StackVec<A>) -> Option<T> {
+    if vec.is_empty() { None } else { Some(vec.remove(0)) }
+}
+
+/// Remove and return the last element, if any.
+#[inline]
+fn pop_last<T, A: Storage<T>>(vec: &mut StackVec<A>) -> Option<T> {
+    if vec.is_empty() { None } else { Some(vec.pop()) }
+}
+
+/// A StackVec whose elements are always in ascending order.
+///
+/// Elements are inserted at their binary-search position, after any equal
+/// elements, so elements that compare equal keep the order in which they were
+/// inserted. No mutable access to the elements is offered, as it could break
+/// the ordering; remove an element and insert it again instead.
+pub struct SortedStackVec<A> {
+    vec: StackVec<A>,
+}
+
+impl<T: Ord, A: ArrayStorage<T>> SortedStackVec<A> {
+
+    /// Create an empty SortedStackVec.
+    #[inline]
+    pub fn new() -> SortedStackVec<A> {
+        SortedStackVec::from_storage(ArrayStorage::new())
+    }
+
+}
+
+impl<T: Ord + Clone, A: ArrayStorage<T>> SortedStackVec<A> {
+
+    /// Merge the elements of the two given SortedStackVecs into a new one.
+    ///
+    /// Fails if the merged elements exceed the capacity.
+    pub fn merge<B: Storage<T>, C: Storage<T>>(a: &SortedStackVec<B>, b: &SortedStackVec<C>)
+        -> SortedStackVec<A>
+    {
+        SortedStackVec { vec: merge_by(a.as_slice(), b.as_slice(), |x, y| *x <= *y) }
+    }
+
+}
+
+impl<T: Ord, A: Storage<T>> SortedStackVec<A> {
+
+    /// Create an empty SortedStackVec over the given storage.
+    #[inline]
+    pub fn from_storage(storage: A) -> SortedStackVec<A> {
+        SortedStackVec { vec: StackVec::from_storage(storage) }
+    }
+
+    /// Return the number of elements.
+    #[inline]
+    pub fn len(&self) -> uint { self.vec.len() }
+
+    /// Return true if the SortedStackVec contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.vec.is_empty() }
+
+    /// Return true if no more elements can be inserted.
+    #[inline]
+    pub fn is_full(&self) -> bool { self.vec.is_full() }
+
+    /// Return the maximum number of elements the SortedStackVec can hold.
+    #[inline]
+    pub fn size(&self) -> uint { self.vec.size() }
+
+    /// Insert the given element at its sorted position, returning that position.
+    ///
+    /// Fails if the SortedStackVec is already full.
+    pub fn insert(&mut self, elem: T) -> uint {
+        match self.try_insert(elem) {
+            Ok(idx) => idx,
+            Err(_) => fail!("SortedStackVec::insert: capacity of {} exceeded", self.size()),
+        }
+    }
+
+    /// Insert the given element at its sorted position, returning that position.
+    ///
+    /// If the SortedStackVec is already full, the element is handed back as `Err`.
+    #[inline]
+    pub fn try_insert(&mut self, elem: T) -> Result<uint, T> {
+        try_insert_by(&mut self.vec, elem, |e, elem| *e <= *elem)
+    }
+
+    /// Return the elements as a slice.
+    #[inline]
+    pub fn as_slice<'a>(&'a self) -> &'a [T] { self.vec.as_slice() }
+
+    /// Return an iterator over the elements in ascending order.
+    #[inline]
+    pub fn iter<'a>(&'a self) -> Items<'a, T> { self.vec.iter() }
+
+    /// Return an immutable reference to the element at the given index.
+    #[inline]
+    pub fn get(&self, idx: uint) -> &T { self.vec.get(idx) }
+
+    /// Return true if an element equal to the given value is present.
+    #[inline]
+    pub fn contains(&self, value: &T) -> bool {
+        let idx = partition_point(self.vec.as_slice(), |e| e < value);
+        idx < self.vec.len() && self.vec.get(idx) == value
+    }
+
+    /// Return the elements that are greater than or equal to `min` and less than `max`.
+    #[inline]
+    pub fn range<'a>(&'a self, min: &T, max: &T) -> &'a [T] {
+        range_by(self.vec.as_slice(), |e| e < min, |e| e < max)
+    }
+
+    /// Return the smallest element.
+    pub fn min(&self) -> Option<&T> {
+        first(&self.vec)
+    }
+
+    /// Return the largest element.
+    pub fn max(&self) -> Option<&T> {
+        last(&self.vec)
+    }
+
+    /// Remove and return the smallest element.
+    pub fn pop_min(&mut self) -> Option<T> {
+        pop_first(&mut self.vec)
+    }
+
+    /// Remove and return the largest element.
+    pub fn pop_max(&mut self) -> Option<T> {
+        pop_last(&mut self.vec)
+    }
+
+    /// Remove and return the element at the given index.
+    #[inline]
+    pub fn remove(&mut self, idx: uint) -> T { self.vec.remove(idx) }
+
+    /// Remove all elements.
+    #[inline]
+    pub fn clear(&mut self) { self.vec.clear() }
+
+}
+
+/// A StackVec whose elements are always in ascending order of the key
+/// returned by the given function, e.g. ordering events by their timestamp.
+///
+/// As with SortedStackVec, elements with equal keys keep the order in which
+/// they were inserted and no mutable access to the elements is offered.
+pub struct SortedStackVecByKey<T, A, K> {
+    vec: StackVec<A>,
+    key: fn(&T) -> K,
+}
+
+impl<T, A: ArrayStorage<T>, K: Ord> SortedStackVecByKey<T, A, K> {
+
+    /// Create an empty SortedStackVecByKey ordered by the given key function.
+    #[inline]
+    pub fn new(key: fn(&T) -> K) -> SortedStackVecByKey<T, A, K> {
+        SortedStackVecByKey::from_storage(ArrayStorage::new(), key)
+    }
+
+}
+
+impl<T: Clone, A: ArrayStorage<T>, K: Ord> SortedStackVecByKey<T, A, K> {
+
+    /// Merge the elements of the two given vectors into a new one, ordered by the key of `a`.
+    ///
+    /// Fails if the merged elements exceed the capacity.
+    pub fn merge<B: Storage<T>, C: Storage<T>>(a: &SortedStackVecByKey<T, B, K>,
+                                               b: &SortedStackVecByKey<T, C, K>)
+        -> SortedStackVecByKey<T, A, K>
+    {
+        let key = a.key;
+        let vec = merge_by(a.as_slice(), b.as_slice(), |x, y| key(x) <= key(y));
+        SortedStackVecByKey { vec: vec, key: key }
+    }
+
+}
+
+impl<T, A: Storage<T>, K: Ord> SortedStackVecByKey<T, A, K> {
+
+    /// Create an empty SortedStackVecByKey over the given storage, ordered by the given key function.
+    #[inline]
+    pub fn from_storage(storage: A, key: fn(&T) -> K) -> SortedStackVecByKey<T, A, K> {
+        SortedStackVecByKey { vec: StackVec::from_storage(storage), key: key }
+    }
+
+    /// Return the number of elements.
+    #[inline]
+    pub fn len(&self) -> uint { self.vec.len() }
+
+    /// Return true if the vector contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.vec.is_empty() }
+
+    /// Return true if no more elements can be inserted.
+    #[inline]
+    pub fn is_full(&self) -> bool { self.vec.is_full() }
+
+    /// Return the maximum number of elements the vector can hold.
+    #[inline]
+    pub fn size(&self) -> uint { self.vec.size() }
+
+    /// Insert the given element at its sorted position, returning that position.
+    ///
+    /// Fails if the vector is already full.
+    pub fn insert(&mut self, elem: T) -> uint {
+        match self.try_insert(elem) {
+            Ok(idx) => idx,
+            Err(_) => fail!("SortedStackVecByKey::insert: capacity of {} exceeded", self.size()),
+        }
+    }
+
+    /// Insert the given element at its sorted position, returning that position.
+    ///
+    /// If the vector is already full, the element is handed back as `Err`.
+    pub fn try_insert(&mut self, elem: T) -> Result<uint, T> {
+        let key = self.key;
+        let elem_key = key(&elem);
+        try_insert_by(&mut self.vec, elem, |e, _| key(e) <= elem_key)
+    }
+
+    /// Return the elements as a slice.
+    #[inline]
+    pub fn as_slice<'a>(&'a self) -> &'a [T] { self.vec.as_slice() }
+
+    /// Return an iterator over the elements in ascending order of their keys.
+    #[inline]
+    pub fn iter<'a>(&'a self) -> Items<'a, T> { self.vec.iter() }
+
+    /// Return an immutable reference to the element at the given index.
+    #[inline]
+    pub fn get(&self, idx: uint) -> &T { self.vec.get(idx) }
+
+    /// Return the elements whose keys are greater than or equal to `min` and less than `max`.
+    pub fn range<'a>(&'a self, min: &K, max: &K) -> &'a [T] {
+        let key = self.key;
+        range_by(self.vec.as_slice(), |e| key(e) < *min, |e| key(e) < *max)
+    }
+
+    /// Return the element with the smallest key.
+    pub fn min(&self) -> Option<&T> {
+        first(&self.vec)
+    }
+
+    /// Return the element with the largest key.
+    pub fn max(&self) -> Option<&T> {
+        last(&self.vec)
+    }
+
+    /// Remove and return the element with the smallest key.
+    pub fn pop_min(&mut self) -> Option<T> {
+        pop_first(&mut self.vec)
+    }
+
+    /// Remove and return the element with the largest key.
+    pub fn pop_max(&mut self) -> Option<T> {
+        pop_last(&mut self.vec)
+    }
+
+    /// Remove and return the element at the given index.
+    #[inline]
+    pub fn remove(&mut self, idx: uint) -> T { self.vec.remove(idx) }
+
+    /// Remove all elements.
+    #[inline]
+    pub fn clear(&mut self) { self.vec.clear() }
+
+}
+
+
+#[test]
+fn ordered_insertion_and_merge() {
+    use {N4, N8};
+
+    let mut a: SortedStackVec<N4<uint>> = SortedStackVec::new();
+    let mut b: SortedStackVec<N4<uint>> = SortedStackVec::new();
+    for &x in [5u, 1, 3].iter() { a.insert(x); }
+    for &x in [4u, 2, 6].iter() { b.insert(x); }
+    assert_eq!(a.as_slice(), [1u, 3, 5].as_slice());
+    assert_eq!(a.range(&2, &5), [3u].as_slice());
+
+    let mut merged: SortedStackVec<N8<uint>> = SortedStackVec::merge(&a, &b);
+    assert_eq!(merged.as_slice(), [1u, 2, 3, 4, 5, 6].as_slice());
+    assert_eq!(merged.pop_min(), Some(1));
+    assert_eq!(merged.pop_max(), Some(6));
+
+    fn time(event: &(uint, char)) -> uint { let &(t, _) = event; t }
+    let mut events: SortedStackVecByKey<(uint, char), N4<(uint, char)>, uint> =
+        SortedStackVecByKey::new(time);
+    events.insert((20, 'b'));
+    events.insert((10, 'a'));
+    events.insert((20, 'c'));
+    assert_eq!(events.range(&15, &30), [(20u, 'b'), (20u, 'c')].as_slice());
+    assert_eq!(events.pop_min(), Some((10, 'a')));
+}
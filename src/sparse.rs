@@ -0,0 +1,289 @@
+//! A vector of optional slots that may be filled and emptied in any order.
+
+use std::mem;
+use StackVecArray;
+
+/// A fixed-size vector whose slots may each be occupied or vacant.
+///
+/// Unlike a StackVec, elements keep their index for as long as they are
+/// stored, and removing an element leaves a hole rather than shifting those
+/// that follow it, which suits fixed-slot collections such as mixer channels.
+pub struct SparseStackVec<A> {
+    len: uint,
+    slots: A,
+}
+
+impl<T, A: StackVecArray<T>> SparseStackVec<A> {
+
+    /// Create a SparseStackVec with every slot vacant.
+    #[inline]
+    pub fn new() -> SparseStackVec<A> {
+        SparseStackVec { len: 0, slots: StackVecArray::new() }
+    }
+
+    /// Return the number of occupied slots.
+    #[inline]
+    pub fn len(&self) -> uint { self.len }
+
+    /// Return true if every slot is vacant.
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.len == 0 }
+
+    /// Return true if every slot is occupied.
+    #[inline]
+    pub fn is_full(&self) -> bool { self.len == self.slots.size() }
+
+    /// Return the number of slots.
+    #[inline]
+    pub fn size(&self) -> uint { self.slots.size() }
+
+    /// Return true if the slot at the given index holds an element.
+    #[inline]
+    pub fn is_occupied(&self, idx: uint) -> bool { self.slots.is_occupied(idx) }
+
+    /// Return an immutable reference to the element at the given index, if any.
+    #[inline]
+    pub fn get(&self, idx: uint) -> Option<&T> {
+        if self.slots.is_occupied(idx) { Some(self.slots.get(idx)) } else { None }
+    }
+
+    /// Return a mutable reference to the element at the given index, if any.
+    #[inline]
+    pub fn get_mut(&mut self, idx: uint) -> Option<&mut T> {
+        if self.slots.is_occupied(idx) { Some(self.slots.get_mut(idx)) } else { None }
+    }
+
+    /// Fill the slot at the given index with the given element, returning the
+    /// element it previously held, if any.
+    pub fn set(&mut self, idx: uint, elem: T) -> Option<T> {
+        let old = self.remove(idx);
+        self.slots.set(idx, elem);
+        self.len += 1;
+        old
+    }
+
+    /// Empty the slot at the given index, returning the element it held, if any.
+    pub fn remove(&mut self, idx: uint) -> Option<T> {
+        if self.slots.is_occupied(idx) {
+            self.len -= 1;
+            Some(self.slots.remove(idx))
+        } else {
+            None
+        }
+    }
+
+    /// Return the index of the first vacant slot.
+    pub fn first_free_slot(&self) -> Option<uint> {
+        range(0, self.slots.size()).find(|&idx| !self.slots.is_occupied(idx))
+    }
+
+    /// Fill the first vacant slot with the given element, returning its index.
+    ///
+    /// If every slot is occupied, the element is handed back as `Err`.
+    pub fn try_push(&mut self, elem: T) -> Result<uint, T> {
+        match self.first_free_slot() {
+            Some(idx) => {
+                self.set(idx, elem);
+                Ok(idx)
+            },
+            None => Err(elem),
+        }
+    }
+
+    /// Return a view into the slot at the given index, for filling or emptying it.
+    #[inline]
+    pub fn entry<'a>(&'a mut self, idx: uint) -> Entry<'a, T, A> {
+        if self.slots.is_occupied(idx) {
+            Occupied(OccupiedEntry { vec: self, idx: idx })
+        } else {
+            Vacant(VacantEntry { vec: self, idx: idx })
+        }
+    }
+
+    /// Swap the contents of the two given slots, whether occupied or vacant.
+    pub fn swap_slots(&mut self, a: uint, b: uint) {
+        let elem_a = self.remove(a);
+        let elem_b = self.remove(b);
+        match elem_a {
+            Some(elem) => { self.set(b, elem); },
+            None => (),
+        }
+        match elem_b {
+            Some(elem) => { self.set(a, elem); },
+            None => (),
+        }
+    }
+
+    /// Return an iterator over the index and element of each occupied slot.
+    #[inline]
+    pub fn iter<'a>(&'a self) -> OccupiedSlots<'a, T, A> {
+        OccupiedSlots { vec: self, idx: 0, remaining: self.len }
+    }
+
+    /// Move all elements into the lowest slots, keeping their order, so that
+    /// the occupied slots are `0` to `len() - 1`.
+    pub fn compact(&mut self) {
+        let mut dst = 0;
+        for src in range(0, self.slots.size()) {
+            if self.slots.is_occupied(src) {
+                if src != dst {
+                    let elem = self.slots.remove(src);
+                    self.slots.set(dst, elem);
+                }
+                dst += 1;
+            }
+        }
+    }
+
+    /// Empty every slot.
+    pub fn clear(&mut self) {
+        for idx in range(0, self.slots.size()) {
+            self.remove(idx);
+        }
+    }
+
+}
+
+/// A view into a single slot of a SparseStackVec.
+pub enum Entry<'a, T: 'a, A: 'a> {
+    /// The slot holds an element.
+    Occupied(OccupiedEntry<'a, T, A>),
+    /// The slot is vacant.
+    Vacant(VacantEntry<'a, T, A>),
+}
+
+/// A view into an occupied slot of a SparseStackVec.
+pub struct OccupiedEntry<'a, T: 'a, A: 'a> {
+    vec: &'a mut SparseStackVec<A>,
+    idx: uint,
+}
+
+/// A view into a vacant slot of a SparseStackVec.
+pub struct VacantEntry<'a, T: 'a, A: 'a> {
+    vec: &'a mut SparseStackVec<A>,
+    idx: uint,
+}
+
+impl<'a, T, A: StackVecArray<T>> Entry<'a, T, A> {
+
+    /// Return the index of the slot.
+    pub fn index(&self) -> uint {
+        match *self {
+            Occupied(ref entry) => entry.idx,
+            Vacant(ref entry) => entry.idx,
+        }
+    }
+
+    /// Return a mutable reference to the slot's element, first filling the
+    /// slot with the result of `f` if it is vacant.
+    pub fn or_insert_with(self, f: || -> T) -> &'a mut T {
+        match self {
+            Occupied(entry) => entry.into_mut(),
+            Vacant(entry) => entry.insert(f()),
+        }
+    }
+
+}
+
+impl<'a, T, A: StackVecArray<T>> OccupiedEntry<'a, T, A> {
+
+    /// Return an immutable reference to the slot's element.
+    pub fn get(&self) -> &T { self.vec.slots.get(self.idx) }
+
+    /// Return a mutable reference to the slot's element.
+    pub fn get_mut(&mut self) -> &mut T { self.vec.slots.get_mut(self.idx) }
+
+    /// Convert the entry into a mutable reference to the slot's element.
+    pub fn into_mut(self) -> &'a mut T {
+        let OccupiedEntry { vec, idx } = self;
+        vec.slots.get_mut(idx)
+    }
+
+    /// Replace the slot's element, returning the old element.
+    pub fn insert(&mut self, elem: T) -> T {
+        mem::replace(self.get_mut(), elem)
+    }
+
+    /// Empty the slot, returning its element.
+    pub fn take(self) -> T {
+        self.vec.remove(self.idx).unwrap()
+    }
+
+}
+
+impl<'a, T, A: StackVecArray<T>> VacantEntry<'a, T, A> {
+
+    /// Fill the slot with the given element, returning a mutable reference to it.
+    pub fn insert(self, elem: T) -> &'a mut T {
+        let VacantEntry { vec, idx } = self;
+        vec.set(idx, elem);
+        vec.slots.get_mut(idx)
+    }
+
+}
+
+/// An iterator over the index and element of each occupied slot of a SparseStackVec.
+pub struct OccupiedSlots<'a, T: 'a, A: 'a> {
+    vec: &'a SparseStackVec<A>,
+    idx: uint,
+    remaining: uint,
+}
+
+impl<'a, T, A: StackVecArray<T>> Iterator<(uint, &'a T)> for OccupiedSlots<'a, T, A> {
+    #[inline]
+    fn next(&mut self) -> Option<(uint, &'a T)> {
+        while self.idx < self.vec.size() {
+            let idx = self.idx;
+            self.idx += 1;
+            if self.vec.slots.is_occupied(idx) {
+                self.remaining -= 1;
+                return Some((idx, self.vec.slots.get(idx)));
+            }
+        }
+        None
+    }
+    #[inline]
+    fn size_hint(&self) -> (uint, Option<uint>) { (self.remaining, Some(self.remaining)) }
+}
+
+
+#[test]
+fn holes_and_compaction() {
+    use Slots4;
+
+    let mut channels: SparseStackVec<Slots4<&'static str>> = SparseStackVec::new();
+    assert_eq!(channels.set(2, "bass"), None);
+    assert_eq!(channels.try_push("drums"), Ok(0));
+    assert_eq!(channels.get(1), None);
+    assert_eq!(channels.set(2, "keys"), Some("bass"));
+    assert_eq!(channels.len(), 2);
+    assert_eq!(channels.iter().collect::<Vec<(uint, &&str)>>(), vec![(0, &"drums"), (2, &"keys")]);
+
+    channels.remove(0);
+    channels.compact();
+    assert_eq!(channels.get(0), Some(&"keys"));
+    assert!(!channels.is_occupied(2));
+}
+
+#[test]
+fn slot_entries() {
+    use Slots4;
+
+    let mut voices: SparseStackVec<Slots4<uint>> = SparseStackVec::new();
+    *voices.entry(1).or_insert_with(|| 60) += 1;
+    assert_eq!(*voices.entry(1).or_insert_with(|| 0), 61);
+    assert_eq!(voices.first_free_slot(), Some(0));
+
+    voices.swap_slots(1, 3);
+    assert_eq!(voices.get(3), Some(&61));
+    assert_eq!(voices.len(), 1);
+    match voices.entry(3) {
+        Occupied(entry) => assert_eq!(entry.take(), 61),
+        Vacant(_) => fail!("slot 3 should be occupied"),
+    }
+    match voices.entry(0) {
+        Vacant(entry) => { entry.insert(64); },
+        Occupied(_) => fail!("slot 0 should be vacant"),
+    }
+    assert_eq!(voices.iter().map(|(i, v)| (i, *v)).collect::<Vec<(uint, uint)>>(), vec![(0, 64)]);
+}
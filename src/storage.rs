@@ -0,0 +1,139 @@
+//! The raw storage over which a StackVec is built.
+//!
+//! A StackVec only needs a pointer to some contiguous slots and to know how
+//! many of them there are. Implementing `Storage` for your own type allows a
+//! StackVec to live within an aligned buffer, a shared memory region or a
+//! `static mut` array.
+
+use std::mem;
+use std::ptr;
+
+/// Raw, fixed-capacity storage for the elements of a StackVec.
+///
+/// Implementors must uphold the following contract, which the StackVec relies
+/// upon for memory safety:
+///
+/// - `as_ptr` and `as_mut_ptr` return a pointer to `capacity()` contiguous,
+///   properly aligned slots of `T`, which stays valid and never changes for as
+///   long as the storage lives.
+/// - `capacity()` never changes.
+/// - The slots are treated as uninitialised. The storage must never read, drop
+///   or move the values within them; the StackVec owns the elements it has
+///   written and drops them itself.
+pub trait Storage<T> {
+    /// Return a pointer to the first slot.
+    fn as_ptr(&self) -> *const T;
+    /// Return a mutable pointer to the first slot.
+    fn as_mut_ptr(&mut self) -> *mut T;
+    /// Return the number of slots.
+    fn capacity(&self) -> uint;
+}
+
+/// Storage that can be created on demand, allowing for `StackVec::new`.
+pub trait ArrayStorage<T>: Storage<T> {
+    /// Create the storage with all of its slots uninitialised.
+    fn new() -> Self;
+}
+
+/// An unsigned integer type in which a StackVec may store its length.
+///
+/// A StackVec stores its length as a `uint` by default, but a smaller type may
+/// be chosen for StackVecs of small capacity, e.g. `StackVec<N8<f32>, u8>`, to
+/// shrink them when storing many at once.
+///
+/// Only the length itself shrinks. An Array keeps its slots behind the tag of
+/// its private `Flag` enum, and both the Array and the StackVec carry a hidden
+/// drop flag, so a `Compact8<u8>` still occupies a few bytes more than its
+/// eight elements and one byte of length.
+pub trait Length: Copy {
+    /// Convert the given length, which must not exceed `max`, into this type.
+    fn from_uint(n: uint) -> Self;
+    /// Convert the length into a `uint`.
+    fn to_uint(self) -> uint;
+    /// Return the greatest length that this type can represent.
+    fn max(_: Option<Self>) -> uint;
+}
+
+impl_length!(u8)
+impl_length!(u16)
+impl_length!(u32)
+impl_length!(uint)
+
+/// The slots of an Array, preceded by a zero-length array of `M` that takes
+/// up no space but raises their alignment to that of `M`.
+#[repr(C)]
+struct Slots<A, M> {
+    _align: [M, ..0],
+    array: A,
+}
+
+/// Tracks whether an Array still owns its slots.
+enum Flag<A> {
+    Alive(A),
+    Dropped,
+}
+
+/// A fixed-size array of uninitialised slots.
+///
+/// The array never drops its contents: those slots that hold elements are
+/// dropped by the StackVec that owns it.
+///
+/// The first slot is aligned to at least the alignment of `M`, which defaults
+/// to `()` and so adds nothing to the alignment of the elements. As the
+/// alignment is applied to the slots themselves rather than to the Array as a
+/// whole, it holds regardless of the Array's drop flag.
+pub struct Array<A, M = ()> {
+    slots: Flag<Slots<A, M>>,
+}
+
+impl<A, M> Array<A, M> {
+
+    /// Create an Array whose slots are all uninitialised.
+    #[inline]
+    pub fn uninitialized() -> Array<A, M> {
+        Array { slots: Alive(Slots { _align: [], array: unsafe { mem::uninitialized() } }) }
+    }
+
+    /// Return the guaranteed alignment of the first slot in bytes.
+    #[inline]
+    pub fn alignment(&self) -> uint { mem::min_align_of::<Slots<A, M>>() }
+
+    /// Return an immutable reference to the underlying array.
+    #[inline]
+    fn array(&self) -> &A {
+        match self.slots {
+            Alive(ref slots) => &slots.array,
+            Dropped => unreachable!(),
+        }
+    }
+
+    /// Return a mutable reference to the underlying array.
+    #[inline]
+    fn array_mut(&mut self) -> &mut A {
+        match self.slots {
+            Alive(ref mut slots) => &mut slots.array,
+            Dropped => unreachable!(),
+        }
+    }
+
+}
+
+#[unsafe_destructor]
+impl<A, M> Drop for Array<A, M> {
+    fn drop(&mut self) {
+        // Overwrite the slots without running their destructors, so that the
+        // uninitialised slots are never dropped.
+        unsafe { ptr::write(&mut self.slots, Dropped) }
+    }
+}
+
+impl_array_storage!(2)
+impl_array_storage!(4)
+impl_array_storage!(8)
+impl_array_storage!(16)
+impl_array_storage!(32)
+impl_array_storage!(64)
+impl_array_storage!(128)
+impl_array_storage!(256)
+impl_array_storage!(512)
+impl_array_storage!(1024)
@@ -0,0 +1,230 @@
+//! A fixed-capacity UTF-8 string and allocation-free formatting, e.g. for
+//! logging from real-time threads.
+//!
+//! Text may be formatted into a StackString, a StackVec of bytes, or a
+//! StackVec of chars through its `char_writer`.
+
+use std::fmt;
+use std::fmt::FormatWriter;
+use std::str;
+use {ArrayStorage, Length, StackVec, Storage};
+
+/// What to do when formatted text does not fit within a StackString.
+#[deriving(Clone, PartialEq, Eq, Show)]
+pub enum Overflow {
+    /// Keep as much of the text as fits, cut at a character boundary.
+    Truncate,
+    /// Return a `fmt::WriteError`.
+    Reject,
+}
+
+/// A UTF-8 string whose bytes live within a StackVec.
+pub struct StackString<A> {
+    vec: StackVec<A>,
+}
+
+impl<A: ArrayStorage<u8>> StackString<A> {
+
+    /// Create an empty StackString.
+    #[inline]
+    pub fn new() -> StackString<A> {
+        StackString::from_storage(ArrayStorage::new())
+    }
+
+    /// Format the given arguments into a new StackString, handling text that
+    /// does not fit according to the given policy.
+    ///
+    /// See the `stack_format!` macro for a more convenient way to call this.
+    pub fn format(overflow: Overflow, args: &fmt::Arguments) -> Result<StackString<A>, fmt::WriteError> {
+        let mut string = StackString::new();
+        match (fmt::write(&mut string, args), overflow) {
+            (Ok(()), _) | (Err(_), Truncate) => Ok(string),
+            (Err(err), Reject) => Err(err),
+        }
+    }
+
+}
+
+impl<A: Storage<u8>> StackString<A> {
+
+    /// Create an empty StackString over the given byte storage.
+    #[inline]
+    pub fn from_storage(storage: A) -> StackString<A> {
+        StackString { vec: StackVec::from_storage(storage) }
+    }
+
+    /// Return the string as a `&str`.
+    #[inline]
+    pub fn as_slice<'a>(&'a self) -> &'a str {
+        // Only whole UTF-8 strings are ever pushed onto the bytes.
+        unsafe { str::raw::from_utf8(self.vec.as_slice()) }
+    }
+
+    /// Return the length of the string in bytes.
+    #[inline]
+    pub fn len(&self) -> uint { self.vec.len() }
+
+    /// Return true if the string is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.vec.is_empty() }
+
+    /// Return the maximum length of the string in bytes.
+    #[inline]
+    pub fn size(&self) -> uint { self.vec.size() }
+
+    /// Append the given string.
+    ///
+    /// Fails if it does not fit.
+    pub fn push_str(&mut self, s: &str) {
+        match self.try_push_str(s) {
+            Ok(()) => (),
+            Err(_) => fail!("StackString::push_str: capacity of {} bytes exceeded", self.size()),
+        }
+    }
+
+    /// Append the given string, or hand it back as `Err` if it does not fit.
+    pub fn try_push_str<'a>(&mut self, s: &'a str) -> Result<(), &'a str> {
+        if s.len() > self.size() - self.len() {
+            return Err(s);
+        }
+        self.vec.write_bytes(s.as_bytes());
+        Ok(())
+    }
+
+    /// Append as much of the given string as fits, cut at a character
+    /// boundary, returning true if all of it fit.
+    pub fn push_str_truncated(&mut self, s: &str) -> bool {
+        let mut end = ::std::cmp::min(s.len(), self.size() - self.len());
+        while !s.is_char_boundary(end) {
+            end -= 1;
+        }
+        self.vec.write_bytes(s.slice_to(end).as_bytes());
+        end == s.len()
+    }
+
+    /// Append the given character.
+    ///
+    /// Fails if it does not fit.
+    pub fn push(&mut self, c: char) {
+        match self.try_push(c) {
+            Ok(()) => (),
+            Err(_) => fail!("StackString::push: capacity of {} bytes exceeded", self.size()),
+        }
+    }
+
+    /// Append the given character, or hand it back as `Err` if it does not fit.
+    pub fn try_push(&mut self, c: char) -> Result<(), char> {
+        let mut buf = [0u8, ..4];
+        let n = c.encode_utf8(buf).unwrap();
+        if n > self.size() - self.len() {
+            return Err(c);
+        }
+        self.vec.write_bytes(buf.slice_to(n));
+        Ok(())
+    }
+
+    /// Remove and return the final character.
+    pub fn pop(&mut self) -> Option<char> {
+        let (idx, c) = match self.as_slice().char_indices().next_back() {
+            Some(last) => last,
+            None => return None,
+        };
+        while self.vec.len() > idx {
+            self.vec.pop();
+        }
+        Some(c)
+    }
+
+    /// Remove all characters.
+    #[inline]
+    pub fn clear(&mut self) { self.vec.clear() }
+
+}
+
+impl<A: Storage<u8>> FormatWriter for StackString<A> {
+    /// Writes as much of the text as fits, returning an error if any was cut.
+    fn write(&mut self, bytes: &[u8]) -> fmt::Result {
+        let s = match str::from_utf8(bytes) {
+            Some(s) => s,
+            None => return Err(fmt::WriteError),
+        };
+        if self.push_str_truncated(s) { Ok(()) } else { Err(fmt::WriteError) }
+    }
+}
+
+impl<A: Storage<u8>, L: Length> FormatWriter for StackVec<A, L> {
+    /// Writes as many bytes as fit, returning an error if any were cut.
+    fn write(&mut self, bytes: &[u8]) -> fmt::Result {
+        if self.write_bytes(bytes) == bytes.len() { Ok(()) } else { Err(fmt::WriteError) }
+    }
+}
+
+/// A FormatWriter that pushes each formatted character onto a StackVec of chars.
+///
+/// A StackVec of bytes implements FormatWriter itself, and as it may not have
+/// a second implementation for chars, they are written through this adapter,
+/// which is returned by `StackVec::char_writer`.
+pub struct CharWriter<'a, A: 'a, L: 'a> {
+    vec: &'a mut StackVec<A, L>,
+}
+
+impl<A: Storage<char>, L: Length> StackVec<A, L> {
+
+    /// Return a FormatWriter that pushes each formatted character onto the StackVec.
+    #[inline]
+    pub fn char_writer<'a>(&'a mut self) -> CharWriter<'a, A, L> {
+        CharWriter { vec: self }
+    }
+
+}
+
+impl<'a, A: Storage<char>, L: Length> FormatWriter for CharWriter<'a, A, L> {
+    /// Pushes as many characters as fit, returning an error if any were cut.
+    fn write(&mut self, bytes: &[u8]) -> fmt::Result {
+        let s = match str::from_utf8(bytes) {
+            Some(s) => s,
+            None => return Err(fmt::WriteError),
+        };
+        for c in s.chars() {
+            if self.vec.try_push(c).is_err() {
+                return Err(fmt::WriteError);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<A: Storage<u8>> fmt::Show for StackString<A> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.as_slice().fmt(f)
+    }
+}
+
+
+#[test]
+fn format_with_overflow_policy() {
+    use {N4, N8};
+
+    let fits: StackString<N8<u8>> =
+        format_args!(|args| StackString::format(Reject, args), "{}-{}", 12u, "ab").unwrap();
+    assert_eq!(fits.as_slice(), "12-ab");
+
+    let reject: Result<StackString<N8<u8>>, fmt::WriteError> =
+        format_args!(|args| StackString::format(Reject, args), "{}", "héllo wörld");
+    assert!(reject.is_err());
+
+    let truncated: StackString<N8<u8>> =
+        format_args!(|args| StackString::format(Truncate, args), "{}", "héllo wörld").unwrap();
+    assert_eq!(truncated.as_slice(), "héllo w");
+
+    let mut s: StackString<N8<u8>> = StackString::new();
+    s.push_str("ab");
+    s.push('ö');
+    assert_eq!(s.pop(), Some('ö'));
+    assert_eq!(s.try_push_str("toolong"), Err("toolong"));
+
+    let mut chars: StackVec<N4<char>> = StackVec::new();
+    let result = format_args!(|args| fmt::write(&mut chars.char_writer(), args), "{}{}", 'a', "öüxy");
+    assert!(result.is_err());
+    assert_eq!(chars.as_slice(), ['a', 'ö', 'ü', 'x'].as_slice());
+}
@@ -0,0 +1,168 @@
+//! A compact, dependency-free binary encoding of StackVecs for wire protocols.
+//!
+//! A StackVec is encoded as its length in LEB128 varint form followed by each
+//! of its elements in little-endian byte order.
+
+use std::mem;
+use {ArrayStorage, Length, StackVec, Storage};
+
+/// The ways in which decoding a StackVec may fail.
+#[deriving(Clone, PartialEq, Eq, Show)]
+pub enum DecodeError {
+    /// The input ended before the whole StackVec was read.
+    Truncated,
+    /// The varint length prefix is longer than any valid length.
+    Overlong,
+    /// The encoded length exceeds the capacity of the StackVec.
+    CapacityExceeded,
+}
+
+/// Types that may be written to and read from the wire as little-endian bytes.
+pub trait WireElem {
+    /// Write the value into the start of `buf`, returning the number of bytes
+    /// written, or `None` if `buf` is too small.
+    fn encode_le(&self, buf: &mut [u8]) -> Option<uint>;
+    /// Read a value from the start of `buf`, returning it along with the
+    /// number of bytes read, or `None` if `buf` is too small.
+    fn decode_le(buf: &[u8]) -> Option<(Self, uint)>;
+}
+
+impl_wire_elem_int!(u8, 1)
+impl_wire_elem_int!(u16, 2)
+impl_wire_elem_int!(u32, 4)
+impl_wire_elem_int!(u64, 8)
+impl_wire_elem_int!(i8, 1)
+impl_wire_elem_int!(i16, 2)
+impl_wire_elem_int!(i32, 4)
+impl_wire_elem_int!(i64, 8)
+
+impl WireElem for f32 {
+    #[inline]
+    fn encode_le(&self, buf: &mut [u8]) -> Option<uint> {
+        let bits: u32 = unsafe { mem::transmute(*self) };
+        bits.encode_le(buf)
+    }
+    #[inline]
+    fn decode_le(buf: &[u8]) -> Option<(f32, uint)> {
+        WireElem::decode_le(buf).map(|(bits, n): (u32, uint)| (unsafe { mem::transmute(bits) }, n))
+    }
+}
+
+impl WireElem for f64 {
+    #[inline]
+    fn encode_le(&self, buf: &mut [u8]) -> Option<uint> {
+        let bits: u64 = unsafe { mem::transmute(*self) };
+        bits.encode_le(buf)
+    }
+    #[inline]
+    fn decode_le(buf: &[u8]) -> Option<(f64, uint)> {
+        WireElem::decode_le(buf).map(|(bits, n): (u64, uint)| (unsafe { mem::transmute(bits) }, n))
+    }
+}
+
+/// Write the given value as a LEB128 varint, returning the number of bytes
+/// written, or `None` if `buf` is too small.
+pub fn encode_varint(mut value: u64, buf: &mut [u8]) -> Option<uint> {
+    let mut i = 0;
+    loop {
+        if i == buf.len() {
+            return None;
+        }
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            buf[i] = byte;
+            return Some(i + 1);
+        }
+        buf[i] = byte | 0x80;
+        i += 1;
+    }
+}
+
+/// Read a LEB128 varint, returning it along with the number of bytes read.
+pub fn decode_varint(buf: &[u8]) -> Result<(u64, uint), DecodeError> {
+    let mut value = 0u64;
+    for (i, byte) in buf.iter().enumerate() {
+        if i == 10 || (i == 9 && *byte > 1) {
+            return Err(Overlong);
+        }
+        value |= (*byte as u64 & 0x7f) << (7 * i);
+        if *byte & 0x80 == 0 {
+            return Ok((value, i + 1));
+        }
+    }
+    Err(Truncated)
+}
+
+impl<T: WireElem, A: Storage<T>, L: Length> StackVec<A, L> {
+
+    /// Encode the StackVec into the start of `buf`, returning the number of
+    /// bytes written, or `None` if `buf` is too small.
+    pub fn encode_into(&self, buf: &mut [u8]) -> Option<uint> {
+        let mut pos = match encode_varint(self.len() as u64, buf) {
+            Some(n) => n,
+            None => return None,
+        };
+        for elem in self.iter() {
+            match elem.encode_le(buf.slice_from_mut(pos)) {
+                Some(n) => pos += n,
+                None => return None,
+            }
+        }
+        Some(pos)
+    }
+
+}
+
+impl<T: WireElem, A: ArrayStorage<T>, L: Length> StackVec<A, L> {
+
+    /// Decode a StackVec from the start of `buf`, returning it along with the
+    /// number of bytes read.
+    pub fn decode(buf: &[u8]) -> Result<(StackVec<A, L>, uint), DecodeError> {
+        let (len, mut pos) = try!(decode_varint(buf));
+        let mut vec: StackVec<A, L> = StackVec::new();
+        if len > vec.size() as u64 {
+            return Err(CapacityExceeded);
+        }
+        for _ in range(0, len) {
+            match WireElem::decode_le(buf.slice_from(pos)) {
+                Some((elem, n)) => {
+                    vec.push(elem);
+                    pos += n;
+                },
+                None => return Err(Truncated),
+            }
+        }
+        Ok((vec, pos))
+    }
+
+}
+
+
+#[test]
+fn encode_decode_round_trip() {
+    use {N2, N4, N256};
+
+    let mut vec: StackVec<N256<i16>> = StackVec::new();
+    for i in range(0i16, 200) {
+        vec.push(i * -3);
+    }
+    let mut buf = [0u8, ..512];
+    let n = vec.encode_into(buf.as_mut_slice()).unwrap();
+    assert_eq!(n, 2 + 200 * 2);
+    assert_eq!(buf.slice_to(2), [0xc8u8, 0x01].as_slice());
+
+    let (decoded, read): (StackVec<N256<i16>>, uint) = StackVec::decode(buf.as_slice()).unwrap();
+    assert_eq!(read, n);
+    assert_eq!(decoded.as_slice(), vec.as_slice());
+    assert!(vec.encode_into(buf.slice_to_mut(100)).is_none());
+
+    let mut floats: StackVec<N4<f32>> = StackVec::new();
+    floats.push(0.5);
+    floats.push(-2.0);
+    let n = floats.encode_into(buf.as_mut_slice()).unwrap();
+    let short: Result<(StackVec<N4<f32>>, uint), DecodeError> = StackVec::decode(buf.slice_to(n - 1));
+    assert_eq!(short.err(), Some(Truncated));
+    let small: Result<(StackVec<N2<f32>>, uint), DecodeError> = StackVec::decode([3u8].as_slice());
+    assert_eq!(small.err(), Some(CapacityExceeded));
+}
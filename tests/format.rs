@@ -0,0 +1,39 @@
+//! Use the exported `stack_format!` macro from outside the crate.
+
+#![feature(phase)]
+
+#[phase(plugin, link)]
+extern crate stack_vec;
+#[cfg(not(any(windows, target_os = "android")))]
+extern crate libc;
+
+use stack_vec::{Reject, Truncate};
+
+#[cfg(not(any(windows, target_os = "android")))]
+#[path = "../src/alloc_check.rs"]
+mod alloc_check;
+
+#[test]
+fn truncate_keeps_what_fits() {
+    let msg = stack_format!(N8, Truncate, "voice {} stolen", 3u).unwrap();
+    assert_eq!(msg.as_slice(), "voice 3 ");
+    let msg = stack_format!(N64, Truncate, "voice {} stolen", 3u).unwrap();
+    assert_eq!(msg.as_slice(), "voice 3 stolen");
+}
+
+#[test]
+fn reject_fails_when_the_text_does_not_fit() {
+    assert!(stack_format!(N8, Reject, "voice {} stolen", 3u).is_err());
+    let msg = stack_format!(N64, Reject, "voice {} stolen", 3u).unwrap();
+    assert_eq!(msg.as_slice(), "voice 3 stolen");
+}
+
+#[test]
+#[cfg(not(any(windows, target_os = "android")))]
+fn stack_format_does_not_allocate() {
+    alloc_check::assert_no_alloc(|| {
+        let msg = stack_format!(N64, Truncate, "voice {} stolen", 3u).unwrap();
+        assert_eq!(msg.len(), 14);
+        assert!(stack_format!(N8, Reject, "voice {} stolen", 3u).is_err());
+    });
+}
@@ -0,0 +1,177 @@
+//! Apply random sequences of operations to both a StackVec and a Vec and check
+//! that they always agree, for every capacity from `N2` to `N1024`.
+//!
+//! The operations are drawn from a generator with a fixed seed, so that any
+//! failing sequence is reproduced on every run.
+
+#![feature(macro_rules, unsafe_destructor)]
+
+extern crate stack_vec;
+
+use stack_vec::{ArrayStorage, StackVec};
+use stack_vec::{N2, N4, N8, N16, N32, N64, N128, N256, N512, N1024};
+use std::cell::Cell;
+use std::mem;
+use std::rand::{Rng, SeedableRng, XorShiftRng};
+
+/// The seed from which every test's operations are generated.
+static SEED: [u32, ..4] = [0x5eed, 0x57ac, 0x7ec, 0x1024];
+
+/// The number of operation sequences each test applies.
+static CASES: uint = 64;
+
+/// An operation to apply to both vectors.
+#[deriving(Clone, Show)]
+enum Op {
+    Push(uint),
+    Pop,
+    Insert(uint, uint),
+    Remove(uint),
+    Set(uint, uint),
+    Truncate(uint),
+    Retain(uint),
+    Drain(uint),
+    Clear,
+}
+
+impl Op {
+    fn random<R: Rng>(rng: &mut R) -> Op {
+        let (a, b) = (rng.gen_range(0u, 2048), rng.gen_range(0u, 2048));
+        // Weight the operations towards pushes so that larger capacities fill up.
+        match rng.gen_range(0u, 13) {
+            0 ... 4 => Push(a),
+            5 => Pop,
+            6 => Insert(a, b),
+            7 => Remove(a),
+            8 => Set(a, b),
+            9 => Truncate(a),
+            10 => Retain(a),
+            11 => Drain(a),
+            _ => Clear,
+        }
+    }
+}
+
+/// An element that counts how many times it has been dropped.
+struct Tracked<'a> {
+    val: uint,
+    drops: &'a Cell<uint>,
+}
+
+impl<'a> Tracked<'a> {
+    fn new(val: uint, drops: &'a Cell<uint>) -> Tracked<'a> {
+        Tracked { val: val, drops: drops }
+    }
+}
+
+#[unsafe_destructor]
+impl<'a> Drop for Tracked<'a> {
+    fn drop(&mut self) { self.drops.set(self.drops.get() + 1); }
+}
+
+/// Apply the operations to a StackVec and a Vec, returning false as soon as they disagree.
+fn agrees<'a, A: ArrayStorage<Tracked<'a>>>(ops: &[Op],
+                                             stack_drops: &'a Cell<uint>,
+                                             vec_drops: &'a Cell<uint>) -> bool {
+    {
+        let mut stack: StackVec<A> = StackVec::new();
+        let mut model: Vec<Tracked> = Vec::new();
+        let capacity = stack.size();
+
+        for op in ops.iter() {
+            match *op {
+                Push(v) => match stack.try_push(Tracked::new(v, stack_drops)) {
+                    Ok(()) if model.len() < capacity => model.push(Tracked::new(v, vec_drops)),
+                    // The rejected element has been dropped, so drop its twin too.
+                    Err(_) if model.len() == capacity => drop(Tracked::new(v, vec_drops)),
+                    _ => return false,
+                },
+                Pop => if !model.is_empty() {
+                    if stack.pop().val != model.pop().unwrap().val { return false }
+                },
+                Insert(i, v) => if model.len() < capacity {
+                    let i = i % (model.len() + 1);
+                    stack.insert(i, Tracked::new(v, stack_drops));
+                    model.insert(i, Tracked::new(v, vec_drops));
+                },
+                Remove(i) => if !model.is_empty() {
+                    let i = i % model.len();
+                    if stack.remove(i).val != model.remove(i).unwrap().val { return false }
+                },
+                Set(i, v) => if !model.is_empty() {
+                    let i = i % model.len();
+                    stack.set(i, Tracked::new(v, stack_drops));
+                    *model.get_mut(i) = Tracked::new(v, vec_drops);
+                },
+                Truncate(n) => {
+                    let n = n % (capacity + 1);
+                    stack.truncate(n);
+                    model.truncate(n);
+                },
+                Retain(m) => {
+                    let m = m % 4 + 2;
+                    stack.retain(|t| t.val % m != 0);
+                    model.retain(|t| t.val % m != 0);
+                },
+                Drain(n) => {
+                    // Take only some of the elements, leaving the rest to be
+                    // dropped along with the iterators.
+                    let n = n % (capacity + 1);
+                    let drained: Vec<uint> = stack.drain().take(n).map(|t| t.val).collect();
+                    let expected: Vec<uint> = mem::replace(&mut model, Vec::new())
+                        .into_iter().take(n).map(|t| t.val).collect();
+                    if drained != expected { return false }
+                },
+                Clear => {
+                    stack.clear();
+                    model.clear();
+                },
+            }
+
+            if stack.len() != model.len()
+            || stack.is_empty() != model.is_empty()
+            || stack.is_full() != (model.len() == capacity)
+            || !stack.iter().zip(model.iter()).all(|(a, b)| a.val == b.val)
+            || stack_drops.get() != vec_drops.get() {
+                return false;
+            }
+        }
+    }
+    stack_drops.get() == vec_drops.get()
+}
+
+macro_rules! model_test(
+    ($name:ident, $N:ident) => (
+        #[test]
+        fn $name() {
+            let mut rng: XorShiftRng = SeedableRng::from_seed(SEED);
+            for _ in range(0, CASES) {
+                let len = rng.gen_range(0u, 4096);
+                let ops: Vec<Op> = range(0, len).map(|_| Op::random(&mut rng)).collect();
+                let (stack_drops, vec_drops) = (Cell::new(0u), Cell::new(0u));
+                assert!(agrees::<$N<Tracked>>(ops.as_slice(), &stack_drops, &vec_drops),
+                        "the StackVec and Vec disagree after {}", ops);
+            }
+        }
+    )
+)
+
+model_test!(agrees_with_vec_n2, N2)
+model_test!(agrees_with_vec_n4, N4)
+model_test!(agrees_with_vec_n8, N8)
+model_test!(agrees_with_vec_n16, N16)
+model_test!(agrees_with_vec_n32, N32)
+model_test!(agrees_with_vec_n64, N64)
+model_test!(agrees_with_vec_n128, N128)
+model_test!(agrees_with_vec_n256, N256)
+model_test!(agrees_with_vec_n512, N512)
+model_test!(agrees_with_vec_n1024, N1024)
+
+#[test]
+#[should_fail]
+fn push_beyond_capacity_fails() {
+    let mut vec: StackVec<N2<uint>> = StackVec::new();
+    for i in range(0u, 3) {
+        vec.push(i);
+    }
+}